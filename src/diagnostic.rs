@@ -0,0 +1,460 @@
+//! 解析错误的定位与源码片段
+
+use orion_error::{StructError, ToStructError};
+use std::{fmt::Display, path::Path};
+
+use crate::error::ConfIOReason;
+use crate::format::ConfFormat;
+
+/// 可定位的解析错误
+pub(crate) trait ParseFailure: Display {
+    /// 出错位置 `(行, 列)`，均从 1 开始，列按字符计
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// 不含位置信息的错误描述
+    fn message(&self) -> String {
+        self.to_string()
+    }
+
+    /// 出错字段的键路径，如 `nested_config.retry_count`
+    fn key_path(&self) -> Option<String> {
+        None
+    }
+}
+
+/// 记录了出错键路径的反序列化错误
+#[derive(Debug)]
+pub(crate) struct Tracked<E> {
+    key_path: Option<String>,
+    inner: E,
+}
+
+impl<E> Tracked<E> {
+    /// 转换内部错误，保留键路径
+    #[allow(dead_code)]
+    pub(crate) fn map<F>(self, f: impl FnOnce(E) -> F) -> Tracked<F> {
+        Tracked {
+            key_path: self.key_path,
+            inner: f(self.inner),
+        }
+    }
+
+    /// 与具体字段无关的错误（语法错误、尾随内容等）
+    #[allow(dead_code)]
+    pub(crate) fn untracked(inner: E) -> Self {
+        Self {
+            key_path: None,
+            inner,
+        }
+    }
+}
+
+/// 反序列化并跟踪当前键路径
+pub(crate) fn deserialize_tracked<'de, D, T>(deserializer: D) -> Result<T, Tracked<D::Error>>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let key_path = e.path().to_string();
+        Tracked {
+            // 根节点的路径为 `.`
+            key_path: (key_path != ".").then_some(key_path),
+            inner: e.into_inner(),
+        }
+    })
+}
+
+impl<E: Display> Display for Tracked<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.key_path {
+            Some(key_path) => write!(f, "{key_path}: {}", self.inner),
+            None => write!(f, "{}", self.inner),
+        }
+    }
+}
+
+impl<E: ParseFailure> ParseFailure for Tracked<E> {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        self.inner.location(content)
+    }
+
+    fn message(&self) -> String {
+        let message = self.inner.message();
+        // serde_yaml 会自行在描述前加上路径
+        match &self.key_path {
+            Some(key_path) => message
+                .strip_prefix(&format!("{key_path}: "))
+                .map(str::to_string)
+                .unwrap_or(message),
+            None => message,
+        }
+    }
+
+    fn key_path(&self) -> Option<String> {
+        self.key_path.clone()
+    }
+}
+
+impl<E: ParseFailure + ?Sized> ParseFailure for Box<E> {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        (**self).location(content)
+    }
+
+    fn message(&self) -> String {
+        (**self).message()
+    }
+
+    fn key_path(&self) -> Option<String> {
+        (**self).key_path()
+    }
+}
+
+/// 由解析错误和出错的文本构造 [`ConfIOReason::Parse`]
+pub(crate) fn parse_error<E: ParseFailure>(
+    format: ConfFormat,
+    path: Option<&Path>,
+    content: &str,
+    err: E,
+) -> StructError<ConfIOReason> {
+    let location = err.location(content);
+    let source_line = location
+        .and_then(|(line, _)| content.lines().nth(line.checked_sub(1)?))
+        .map(str::to_string);
+    ConfIOReason::Parse {
+        format,
+        path: path.map(Path::to_path_buf),
+        line: location.map(|(line, _)| line),
+        column: location.map(|(_, column)| column),
+        key_path: err.key_path(),
+        message: err.message(),
+        source_line,
+    }
+    .to_err()
+}
+
+/// 渲染解析错误：摘要行加上带 `^` 标记的出错行
+pub(crate) fn render_parse(
+    format: &ConfFormat,
+    path: &Option<std::path::PathBuf>,
+    line: &Option<usize>,
+    column: &Option<usize>,
+    key_path: &Option<String>,
+    message: &str,
+    source_line: &Option<String>,
+) -> String {
+    let mut out = format!("{format} parse error");
+    if let Some(path) = path {
+        out.push_str(&format!(" in {}", path.display()));
+    }
+    if let (Some(line), Some(column)) = (line, column) {
+        out.push_str(&format!(" at line {line} column {column}"));
+    }
+    out.push_str(": ");
+    if let Some(key_path) = key_path {
+        out.push_str(&format!("{key_path}: "));
+    }
+    out.push_str(message);
+    if let (Some(line), Some(column), Some(text)) = (line, column, source_line) {
+        let gutter = line.to_string().len();
+        // 保留制表符，使 `^` 与出错字符对齐
+        let padding: String = text
+            .chars()
+            .take(column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        out.push_str(&format!("\n {line} | {text}\n {:gutter$} | {padding}^", ""));
+    }
+    out
+}
+
+/// 去掉错误描述末尾的 ` at line L column C`
+#[allow(dead_code)]
+fn strip_location(text: String, line: usize, column: usize) -> String {
+    let suffix = format!(" at line {line} column {column}");
+    match text.find(&suffix) {
+        Some(index) => format!("{}{}", &text[..index], &text[index + suffix.len()..]),
+        None => text,
+    }
+}
+
+/// 字节偏移转换为 `(行, 列)`
+#[allow(dead_code)]
+fn offset_location(content: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = content.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Some((
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    ))
+}
+
+impl ParseFailure for serde_json::Error {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        // 值树反序列化产生的错误没有位置（行号为 0）
+        if self.line() == 0 {
+            return None;
+        }
+        // serde_json 的列按字节计
+        let line_text = content.lines().nth(self.line() - 1).unwrap_or_default();
+        let column = match line_text.get(..self.column().saturating_sub(1)) {
+            Some(prefix) => prefix.chars().count() + 1,
+            None => self.column(),
+        };
+        Some((self.line(), column))
+    }
+
+    fn message(&self) -> String {
+        strip_location(self.to_string(), self.line(), self.column())
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl ParseFailure for serde_yaml::Error {
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        self.location()
+            .map(|location| (location.line(), location.column()))
+    }
+
+    fn message(&self) -> String {
+        match self.location() {
+            Some(location) => strip_location(self.to_string(), location.line(), location.column()),
+            None => self.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl ParseFailure for saphyr_parser::ScanError {
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        // 行从 1 开始，列从 0 开始
+        Some((self.marker().line(), self.marker().col() + 1))
+    }
+
+    fn message(&self) -> String {
+        self.info().to_string()
+    }
+}
+
+#[cfg(feature = "toml")]
+impl ParseFailure for toml::de::Error {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        offset_location(content, self.span()?.start)
+    }
+
+    fn message(&self) -> String {
+        self.message().trim_end().to_string()
+    }
+}
+
+#[cfg(feature = "toml")]
+impl ParseFailure for toml_edit::TomlError {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        offset_location(content, self.span()?.start)
+    }
+
+    fn message(&self) -> String {
+        self.message().trim_end().to_string()
+    }
+}
+
+#[cfg(feature = "json5")]
+impl ParseFailure for json5::Error {
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        let json5::Error::Message { location, .. } = self;
+        location
+            .as_ref()
+            .map(|location| (location.line, location.column))
+    }
+
+    fn message(&self) -> String {
+        let json5::Error::Message { msg, .. } = self;
+        // 语法错误自带源码片段，只保留末行 `= expected ...` 的描述
+        match msg.rsplit_once("\n  = ") {
+            Some((_, expected)) if msg.starts_with(" --> ") => expected.to_string(),
+            _ => msg.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "ron")]
+impl ParseFailure for ron::error::SpannedError {
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        Some((self.span.start.line, self.span.start.col))
+    }
+
+    fn message(&self) -> String {
+        self.code.to_string()
+    }
+}
+
+#[cfg(feature = "hcl")]
+impl ParseFailure for hcl::Error {
+    fn location(&self, content: &str) -> Option<(usize, usize)> {
+        match self {
+            hcl::Error::Parse(err) => offset_location(content, err.location().offset()),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            // 语法错误的 Display 自带源码片段
+            hcl::Error::Parse(err) => err.message().to_string(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// 手写解析器（INI、dotenv、properties）与 RON 值树检查的语法错误，行列均从 1 开始
+#[cfg(any(
+    feature = "ini",
+    feature = "dotenv",
+    feature = "properties",
+    feature = "ron"
+))]
+#[derive(Debug)]
+pub(crate) struct SyntaxError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+#[cfg(any(
+    feature = "ini",
+    feature = "dotenv",
+    feature = "properties",
+    feature = "ron"
+))]
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+#[cfg(any(
+    feature = "ini",
+    feature = "dotenv",
+    feature = "properties",
+    feature = "ron"
+))]
+impl ParseFailure for SyntaxError {
+    fn location(&self, _content: &str) -> Option<(usize, usize)> {
+        Some((self.line, self.column))
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl ParseFailure for rmp_serde::decode::Error {}
+
+#[cfg(feature = "cbor")]
+impl ParseFailure for ciborium::de::Error<std::io::Error> {
+    fn message(&self) -> String {
+        // Display 只输出 Debug 形式
+        match self {
+            ciborium::de::Error::Io(err) => err.to_string(),
+            ciborium::de::Error::Syntax(offset) => format!("invalid CBOR at byte {offset}"),
+            ciborium::de::Error::Semantic(_, message) => message.clone(),
+            ciborium::de::Error::RecursionLimitExceeded => "recursion limit exceeded".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    fn parse_reason<E: ParseFailure>(format: ConfFormat, content: &str, err: E) -> ConfIOReason {
+        parse_error(format, Some(Path::new("conf/app.cfg")), content, err)
+            .reason()
+            .clone()
+    }
+
+    #[test]
+    fn test_parse_error_json_location() {
+        let content = "{\n  \"name\": \"é\",\n  \"port\": x\n}";
+        let err = serde_json::from_str::<Value>(content).unwrap_err();
+        let reason = parse_reason(ConfFormat::Json, content, err);
+        assert_eq!(
+            reason,
+            ConfIOReason::Parse {
+                format: ConfFormat::Json,
+                path: Some(PathBuf::from("conf/app.cfg")),
+                line: Some(3),
+                column: Some(11),
+                key_path: None,
+                message: "expected value".to_string(),
+                source_line: Some("  \"port\": x".to_string()),
+            }
+        );
+        assert_eq!(
+            reason.to_string(),
+            "json parse error in conf/app.cfg at line 3 column 11: expected value\n \
+             3 |   \"port\": x\n   |           ^"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_without_location() {
+        let err = serde_json::from_value::<u16>(Value::from("x")).unwrap_err();
+        let reason = parse_reason(ConfFormat::Json, "", err);
+        assert_eq!(
+            reason.to_string(),
+            "json parse error in conf/app.cfg: invalid type: string \"x\", expected u16"
+        );
+    }
+
+    #[test]
+    fn test_offset_location() {
+        let content = "a = 1\n\tb = é\nc";
+        assert_eq!(offset_location(content, 0), Some((1, 1)));
+        assert_eq!(offset_location(content, 7), Some((2, 2)));
+        assert_eq!(offset_location(content, 14), Some((3, 1)));
+        assert_eq!(offset_location(content, 100), None);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_parse_error_yaml_location() {
+        let content = "name: a\nport: [\n";
+        let err = serde_yaml::from_str::<serde_yaml::Value>(content).unwrap_err();
+        match parse_reason(ConfFormat::Yaml, content, err) {
+            ConfIOReason::Parse {
+                line,
+                column,
+                message,
+                ..
+            } => {
+                assert_eq!(line, Some(3));
+                assert_eq!(column, Some(1));
+                assert!(!message.contains(" at line "));
+            }
+            other => panic!("unexpected reason: {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_parse_error_toml_location() {
+        let content = "name = \"a\"\nport = = 3\n";
+        let err = toml::from_str::<toml::Table>(content).unwrap_err();
+        let reason = parse_reason(ConfFormat::Toml, content, err);
+        assert_eq!(
+            reason.to_string(),
+            "toml parse error in conf/app.cfg at line 2 column 8: extra `=`, expected nothing\n \
+             2 | port = = 3\n   |        ^"
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! `.env` 文件作为 [`EnvDict`] 来源，以及 dotenv 文本的解析
+
+use orion_error::{ContextRecord, ErrorOwe, ErrorWith, OperationContext};
+use orion_variate::{EnvDict, ValueType};
+use std::{fs, path::Path};
+
+use crate::diagnostic::{SyntaxError, parse_error};
+use crate::env::{UnresolvedPolicy, Vars, check_unresolved, expand};
+use crate::error::{OrionConfError, OrionConfResult};
+use crate::format::ConfFormat;
+
+/// 一条键值对及其所在的行
+pub(crate) struct Entry {
+    key: String,
+    parts: Vec<Part>,
+    line: usize,
+}
+
+/// 值片段：单引号内的文本和转义字符是字面量，其余片段参与 `${VAR}` 展开
+enum Part {
+    Literal(String),
+    Expand(String),
+}
+
+/// 按 dotenv 语法解析，值中的 `${VAR}` 留待 [`resolve`] 展开
+///
+/// 支持 `export` 前缀、单双引号、行尾注释和引号内的多行值；
+/// 双引号内和未加引号时 `\$`、`\"`、`\'`、`\\`、`\ ` 表示字面字符，`\n` 表示换行。
+pub(crate) fn parse(content: &str) -> Result<Vec<Entry>, SyntaxError> {
+    let content = content.replace("\r\n", "\n");
+    let mut cursor = Cursor::new(&content);
+    let mut entries = Vec::new();
+    loop {
+        cursor.skip_blank();
+        match cursor.peek() {
+            None => break,
+            Some('\n') => {
+                cursor.bump();
+                continue;
+            }
+            Some('#') => {
+                cursor.skip_line();
+                continue;
+            }
+            _ => {}
+        }
+        let line = cursor.line;
+        let mut key = cursor.key()?;
+        cursor.skip_blank();
+        // `export` 既可以是前缀，也可以是键名
+        if key == "export" && cursor.peek() != Some('=') {
+            key = cursor.key()?;
+            cursor.skip_blank();
+        }
+        if cursor.peek() != Some('=') {
+            return Err(cursor.error("expected `=` after key"));
+        }
+        cursor.bump();
+        cursor.skip_blank();
+        let parts = cursor.value()?;
+        entries.push(Entry { key, parts, line });
+    }
+    Ok(entries)
+}
+
+/// 依次展开各值中的 `${VAR}`，引用取自 `base` 和文件中在前面出现的键（`base` 优先）
+///
+/// 展开结果不会再次解释，变量值中的 `$` 原样保留；未解析的占位符原样保留并记录到 `unresolved`。
+/// `process_env` 为 true 时，两者都没有的变量再查找进程环境变量。
+/// 出错时返回出错条目所在的行。
+pub(crate) fn resolve(
+    entries: Vec<Entry>,
+    base: &EnvDict,
+    process_env: bool,
+    unresolved: &mut Vec<String>,
+) -> Result<Vec<(String, String)>, (usize, OrionConfError)> {
+    let mut scope = base.clone();
+    let mut pairs = Vec::with_capacity(entries.len());
+    for Entry { key, parts, line } in entries {
+        let vars = if process_env {
+            Vars::with_process_env(&scope)
+        } else {
+            Vars::dict_only(&scope)
+        };
+        let mut value = String::new();
+        for part in parts {
+            match part {
+                Part::Literal(text) => value.push_str(&text),
+                Part::Expand(text) => {
+                    value.push_str(&expand(&text, vars, unresolved).map_err(|e| (line, e))?)
+                }
+            }
+        }
+        if base.get_case_insensitive(&key).is_none() {
+            scope.insert(key.clone(), ValueType::String(value.clone()));
+        }
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+/// 键名转为小写以对应 snake_case 字段
+pub(crate) fn to_tree(pairs: Vec<(String, String)>) -> serde_json::Value {
+    serde_json::Value::Object(
+        pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_ascii_lowercase(), serde_json::Value::String(value)))
+            .collect(),
+    )
+}
+
+/// 先解析键值对，再用 `dict`（及进程环境变量）展开值，变量值中的 `$` 不会被再次解释
+pub(crate) fn env_tree(
+    content: &str,
+    path: Option<&Path>,
+    dict: &EnvDict,
+    policy: UnresolvedPolicy,
+) -> OrionConfResult<serde_json::Value> {
+    let entries = parse(content).map_err(|e| parse_error(ConfFormat::Dotenv, path, content, e))?;
+    let mut unresolved = Vec::new();
+    let pairs = resolve(entries, dict, true, &mut unresolved).map_err(|(_, e)| e)?;
+    check_unresolved(unresolved, policy)?;
+    Ok(to_tree(pairs))
+}
+
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            chars: content.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// 下一个字符处的错误
+    fn error(&self, message: &str) -> SyntaxError {
+        SyntaxError {
+            line: self.line,
+            column: self.column,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    fn key(&mut self) -> Result<String, SyntaxError> {
+        if !self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        {
+            return Err(self.error("invalid key"));
+        }
+        let mut key = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.'))
+        {
+            key.push(c);
+            self.bump();
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Vec<Part>, SyntaxError> {
+        let mut parts = Vec::new();
+        if self.peek() == Some('#') {
+            self.skip_line();
+            return Ok(parts);
+        }
+        loop {
+            match self.peek() {
+                None | Some('\n') => break,
+                // 值后面的空白只能跟注释，`k=v#c` 中的 `#` 属于值
+                Some(' ' | '\t') => {
+                    self.skip_blank();
+                    match self.peek() {
+                        None | Some('\n') => break,
+                        Some('#') => {
+                            self.skip_line();
+                            break;
+                        }
+                        _ => return Err(self.error("unquoted value contains whitespace")),
+                    }
+                }
+                Some(quote @ ('\'' | '"')) => {
+                    let unterminated = self.error("unterminated quoted value");
+                    self.bump();
+                    loop {
+                        match self.peek() {
+                            None => return Err(unterminated),
+                            Some(c) if c == quote => {
+                                self.bump();
+                                break;
+                            }
+                            Some('\\') if quote == '"' => self.escape(&mut parts)?,
+                            Some(c) => {
+                                self.bump();
+                                push(&mut parts, c, quote == '\'');
+                            }
+                        }
+                    }
+                }
+                Some('\\') => self.escape(&mut parts)?,
+                Some(c) => {
+                    self.bump();
+                    push(&mut parts, c, false);
+                }
+            }
+        }
+        Ok(parts)
+    }
+
+    fn escape(&mut self, parts: &mut Vec<Part>) -> Result<(), SyntaxError> {
+        self.bump();
+        let invalid = self.error("invalid escape sequence");
+        match self.bump() {
+            Some('n') => push(parts, '\n', true),
+            Some(c @ ('\\' | '\'' | '"' | '$' | ' ')) => push(parts, c, true),
+            _ => return Err(invalid),
+        }
+        Ok(())
+    }
+}
+
+fn push(parts: &mut Vec<Part>, c: char, literal: bool) {
+    match parts.last_mut() {
+        Some(Part::Literal(text)) if literal => text.push(c),
+        Some(Part::Expand(text)) if !literal => text.push(c),
+        _ if literal => parts.push(Part::Literal(c.to_string())),
+        _ => parts.push(Part::Expand(c.to_string())),
+    }
+}
+
+/// 从 `.env` 文件生成 [`EnvDict`]
+///
+/// 默认只包含文件中的变量，`${VAR}` 只引用文件中在前面出现的变量；
+/// 开启 `with_process_env` 后进程环境变量覆盖文件中的同名变量，引用时也优先使用进程环境变量。
+#[derive(Clone, Debug, Default)]
+pub struct DotenvSource {
+    process_env: bool,
+    vars: Option<Vec<(String, String)>>,
+}
+
+impl DotenvSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_process_env(mut self, process_env: bool) -> Self {
+        self.process_env = process_env;
+        self
+    }
+
+    pub fn process_env(&self) -> bool {
+        self.process_env
+    }
+
+    /// 开启 `with_process_env` 时使用给定的变量代替进程环境变量
+    pub fn with_vars<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.vars = Some(
+            vars.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// 读取并解析 `.env` 文件
+    pub fn load(&self, path: &Path) -> OrionConfResult<EnvDict> {
+        let mut ctx = OperationContext::want("load env dict from dotenv").with_auto_log();
+        ctx.record("from path", path);
+        let content = fs::read_to_string(path).owe_res().with(&ctx)?;
+        let dict = self.build(&content, Some(path)).with(&ctx)?;
+        ctx.mark_suc();
+        Ok(dict)
+    }
+
+    /// 解析 `.env` 文本
+    pub fn parse(&self, content: &str) -> OrionConfResult<EnvDict> {
+        self.build(content, None)
+    }
+
+    fn build(&self, content: &str, path: Option<&Path>) -> OrionConfResult<EnvDict> {
+        let entries =
+            parse(content).map_err(|e| parse_error(ConfFormat::Dotenv, path, content, e))?;
+        let mut base = EnvDict::new();
+        if self.process_env {
+            for (key, value) in self.process_vars() {
+                base.insert(key, ValueType::String(value));
+            }
+        }
+        let pairs = resolve(entries, &base, false, &mut Vec::new()).map_err(|(_, e)| e)?;
+        let mut dict = base.clone();
+        for (key, value) in pairs {
+            if base.get_case_insensitive(&key).is_none() {
+                dict.insert(key, ValueType::String(value));
+            }
+        }
+        Ok(dict)
+    }
+
+    fn process_vars(&self) -> Vec<(String, String)> {
+        match &self.vars {
+            Some(vars) => vars.clone(),
+            // 跳过无法表示为 UTF-8 的变量
+            None => std::env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ConfIOReason;
+
+    const CONTENT: &str = r#"# database
+export DB_HOST=db.internal
+DB_PORT = 5432 # default port
+DB_PASS='p@ss ${word}'
+GREETING="hello
+world"
+DB_URL="postgres://${DB_HOST}:${DB_PORT}"
+ESCAPED="cost \$5 \${DB_HOST}"
+"#;
+
+    fn get(dict: &EnvDict, key: &str) -> Option<String> {
+        dict.get(key).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn test_parse_dict() {
+        let dict = DotenvSource::new().parse(CONTENT).unwrap();
+        assert_eq!(get(&dict, "DB_HOST").as_deref(), Some("db.internal"));
+        assert_eq!(get(&dict, "DB_PORT").as_deref(), Some("5432"));
+        assert_eq!(get(&dict, "DB_PASS").as_deref(), Some("p@ss ${word}"));
+        assert_eq!(get(&dict, "GREETING").as_deref(), Some("hello\nworld"));
+        assert_eq!(
+            get(&dict, "DB_URL").as_deref(),
+            Some("postgres://db.internal:5432")
+        );
+        assert_eq!(get(&dict, "ESCAPED").as_deref(), Some("cost $5 ${DB_HOST}"));
+    }
+
+    #[test]
+    fn test_process_env_overrides_file() {
+        let content = "ORION_CONF_DOTENV_TEST=file\nORION_CONF_DOTENV_ONLY=file\nREF=\"${ORION_CONF_DOTENV_TEST}\"\n";
+        let layered = DotenvSource::new()
+            .with_process_env(true)
+            .with_vars([("ORION_CONF_DOTENV_TEST", "process")])
+            .parse(content)
+            .unwrap();
+        assert_eq!(
+            get(&layered, "ORION_CONF_DOTENV_TEST").as_deref(),
+            Some("process")
+        );
+        assert_eq!(
+            get(&layered, "ORION_CONF_DOTENV_ONLY").as_deref(),
+            Some("file")
+        );
+        assert_eq!(get(&layered, "REF").as_deref(), Some("process"));
+
+        let plain = DotenvSource::new()
+            .with_vars([("ORION_CONF_DOTENV_TEST", "process")])
+            .parse(content)
+            .unwrap();
+        assert_eq!(
+            get(&plain, "ORION_CONF_DOTENV_TEST").as_deref(),
+            Some("file")
+        );
+        assert_eq!(get(&plain, "REF").as_deref(), Some("file"));
+    }
+
+    #[test]
+    fn test_lowercase_keys() {
+        let content = "db_host=from-file\nurl=\"${db_host}:${DB_PORT:-5432}\"\nDB_PORT=1\n";
+        let dict = DotenvSource::new().parse(content).unwrap();
+        assert_eq!(get(&dict, "DB_HOST").as_deref(), Some("from-file"));
+        assert_eq!(get(&dict, "URL").as_deref(), Some("from-file:5432"));
+
+        let layered = DotenvSource::new()
+            .with_process_env(true)
+            .with_vars([("DB_HOST", "from-env")])
+            .parse(content)
+            .unwrap();
+        assert_eq!(get(&layered, "DB_HOST").as_deref(), Some("from-env"));
+        assert_eq!(get(&layered, "URL").as_deref(), Some("from-env:5432"));
+    }
+
+    #[test]
+    fn test_references_ignore_process_env_by_default() {
+        // PATH 在进程环境中存在，且与文件中的值不同
+        let content = "PATH=file\nURL=\"${PATH}\"\nHOME_REF=\"${ORION_CONF_DOTENV_UNSET}\"\n";
+        let dict = DotenvSource::new().parse(content).unwrap();
+        assert_eq!(get(&dict, "PATH").as_deref(), Some("file"));
+        assert_eq!(get(&dict, "URL").as_deref(), Some("file"));
+        assert_eq!(
+            get(&dict, "HOME_REF").as_deref(),
+            Some("${ORION_CONF_DOTENV_UNSET}")
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("A=1\nB=\"open\n").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.message, "unterminated quoted value");
+
+        let err = parse("A=\"bad \\q\"\n").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 9));
+
+        let err = parse("1A=x\n").err().unwrap();
+        assert_eq!(err.message, "invalid key");
+    }
+
+    #[test]
+    fn test_load_file_and_error_location() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join(".env");
+        fs::write(&path, CONTENT).unwrap();
+        let dict = DotenvSource::new().load(&path).unwrap();
+        assert_eq!(get(&dict, "DB_HOST").as_deref(), Some("db.internal"));
+
+        fs::write(&path, "A=1\nB=unquoted value\n").unwrap();
+        let err = DotenvSource::new().load(&path).unwrap_err();
+        match err.reason() {
+            ConfIOReason::Parse {
+                format,
+                line,
+                column,
+                source_line,
+                ..
+            } => {
+                assert_eq!(*format, ConfFormat::Dotenv);
+                assert_eq!(*line, Some(2));
+                assert_eq!(*column, Some(12));
+                assert_eq!(source_line.as_deref(), Some("B=unquoted value"));
+            }
+            other => panic!("unexpected reason: {other:?}"),
+        }
+    }
+}
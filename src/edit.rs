@@ -0,0 +1,1091 @@
+//! 保留注释与格式的原位编辑
+
+#[cfg(feature = "toml")]
+pub use self::toml_doc::TomlEditor;
+#[cfg(feature = "yaml")]
+pub use self::yaml_doc::YamlEditor;
+
+#[cfg(feature = "toml")]
+mod toml_doc {
+    use orion_error::{ContextRecord, ErrorOwe, ErrorWith, OperationContext, ToStructError};
+    use std::{
+        fmt::Display,
+        fs,
+        path::{Path, PathBuf},
+    };
+    use toml_edit::{ArrayOfTables, DocumentMut, Item, TableLike, Value};
+
+    use crate::diagnostic::parse_error;
+    use crate::error::{ConfIOReason, OrionConfResult};
+    use crate::format::{ConfFormat, toml_from_str};
+    use crate::persist::save_to_file;
+
+    /// 基于格式保留文档模型的 TOML 编辑器
+    ///
+    /// 只改写发生变化的值，未改动部分的注释、空白和键顺序原样保留。
+    #[derive(Clone, Debug)]
+    pub struct TomlEditor {
+        path: Option<PathBuf>,
+        doc: DocumentMut,
+    }
+
+    impl TomlEditor {
+        /// 读取已有文件
+        pub fn open(path: &Path) -> OrionConfResult<Self> {
+            let mut ctx = OperationContext::want("open toml document").with_auto_log();
+            ctx.record("from path", path);
+            let content = fs::read_to_string(path).owe_res().with(&ctx)?;
+            let doc = content
+                .parse::<DocumentMut>()
+                .map_err(|e| parse_error(ConfFormat::Toml, Some(path), &content, e))
+                .with(&ctx)?;
+            ctx.mark_suc();
+            Ok(Self {
+                path: Some(path.to_path_buf()),
+                doc,
+            })
+        }
+
+        /// 解析内存中的 TOML 文本
+        pub fn parse(content: &str) -> OrionConfResult<Self> {
+            let doc = content
+                .parse::<DocumentMut>()
+                .map_err(|e| parse_error(ConfFormat::Toml, None, content, e))?;
+            Ok(Self { path: None, doc })
+        }
+
+        /// 将当前文档反序列化为 `T`
+        pub fn load<T>(&self) -> OrionConfResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            let content = self.doc.to_string();
+            toml_from_str(&content)
+                .map_err(|e| parse_error(ConfFormat::Toml, self.path.as_deref(), &content, e))
+        }
+
+        /// 设置点分键路径（如 `database.url`）上的值，缺失的中间表会被创建
+        pub fn set<V>(&mut self, key_path: &str, value: &V) -> OrionConfResult<()>
+        where
+            V: serde::Serialize + ?Sized,
+        {
+            let new_value = value
+                .serialize(toml_edit::ser::ValueSerializer::new())
+                .map_err(|e| ConfIOReason::from(e.to_string()).to_err())?;
+            let (parent, key) = self.parent_mut(key_path, true)?;
+            let parent = parent.ok_or_else(|| invalid_path(key_path))?;
+            match parent.get_mut(key) {
+                Some(slot) => replace_item(slot, Item::Value(new_value)),
+                None => {
+                    parent.insert(key, Item::Value(new_value));
+                }
+            }
+            Ok(())
+        }
+
+        /// 删除点分键路径上的值，返回该键是否存在
+        pub fn remove(&mut self, key_path: &str) -> OrionConfResult<bool> {
+            let (parent, key) = self.parent_mut(key_path, false)?;
+            Ok(parent.is_some_and(|parent| parent.remove(key).is_some()))
+        }
+
+        /// 以修改后的 `T` 更新文档
+        ///
+        /// 与文档当前内容对应的 `T` 比较，只写入变化的字段、删除被清空的字段；
+        /// `T` 未建模的键保持不变。文档无法读取为 `T` 时（缺少新增字段、值类型不符）改为与文档
+        /// 自身的值比较，只写入变化的字段，不删除任何键。
+        pub fn update_from<T>(&mut self, value: &T) -> OrionConfResult<()>
+        where
+            T: serde::Serialize + serde::de::DeserializeOwned,
+        {
+            let new = to_document(value)?;
+            let old = match self.load::<T>() {
+                Ok(current) => to_document(&current)?,
+                Err(_) => {
+                    let mut old = to_document(&self.load::<toml::Table>()?)?;
+                    retain_keys(old.as_table_mut(), new.as_table());
+                    old
+                }
+            };
+            merge_table(
+                self.doc.as_table_mut(),
+                old.as_table(),
+                new.as_table(),
+                true,
+            );
+            Ok(())
+        }
+
+        /// 写回打开时的文件
+        pub fn save(&self) -> OrionConfResult<()> {
+            let path = self.path.as_deref().ok_or_else(|| {
+                ConfIOReason::from("toml document has no source path".to_string()).to_err()
+            })?;
+            self.save_to(path)
+        }
+
+        /// 原子写入指定文件
+        pub fn save_to(&self, path: &Path) -> OrionConfResult<()> {
+            save_to_file(path, "toml document", || Ok(self.doc.to_string()))
+        }
+
+        /// 找到键路径的父表；`create` 为真时创建缺失的中间表
+        fn parent_mut<'a>(
+            &mut self,
+            key_path: &'a str,
+            create: bool,
+        ) -> OrionConfResult<(Option<&mut dyn TableLike>, &'a str)> {
+            let segments: Vec<&str> = key_path.split('.').collect();
+            if segments.iter().any(|segment| segment.is_empty()) {
+                return Err(invalid_path(key_path));
+            }
+            let (key, parents) = segments
+                .split_last()
+                .ok_or_else(|| invalid_path(key_path))?;
+            let mut table: &mut dyn TableLike = self.doc.as_table_mut();
+            for segment in parents {
+                if !table.contains_key(segment) {
+                    if !create {
+                        return Ok((None, key));
+                    }
+                    let mut child = toml_edit::Table::new();
+                    child.set_implicit(true);
+                    table.insert(segment, Item::Table(child));
+                }
+                table = table
+                    .get_mut(segment)
+                    .and_then(Item::as_table_like_mut)
+                    .ok_or_else(|| invalid_path(key_path))?;
+            }
+            Ok((Some(table), key))
+        }
+    }
+
+    impl Display for TomlEditor {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.doc)
+        }
+    }
+
+    fn invalid_path(key_path: &str) -> orion_error::StructError<ConfIOReason> {
+        ConfIOReason::from(format!("invalid toml key path `{key_path}`")).to_err()
+    }
+
+    fn to_document<T: serde::Serialize>(value: &T) -> OrionConfResult<DocumentMut> {
+        toml_edit::ser::to_document(value).map_err(|e| ConfIOReason::from(e.to_string()).to_err())
+    }
+
+    /// 三方合并：`old` 为文档当前内容对应的值，`new` 为目标值
+    ///
+    /// `sections` 表示 `existing` 是独立表，新增的表写成 `[section]` 形式。
+    fn merge_table(
+        existing: &mut dyn TableLike,
+        old: &dyn TableLike,
+        new: &dyn TableLike,
+        sections: bool,
+    ) {
+        for (key, new_item) in new.iter() {
+            let old_item = old.get(key);
+            // 两次序列化格式一致，文本相同即值相同
+            if old_item.is_some_and(|old_item| old_item.to_string() == new_item.to_string()) {
+                continue;
+            }
+            let Some(slot) = existing.get_mut(key) else {
+                let item = new_item.clone();
+                existing.insert(key, if sections { into_section(item) } else { item });
+                continue;
+            };
+            match old_item {
+                Some(old_item) if merge_nested(slot, old_item, new_item) => {}
+                _ => replace_item(slot, new_item.clone()),
+            }
+        }
+        let removed: Vec<String> = old
+            .iter()
+            .filter(|(key, _)| !new.contains_key(key))
+            .map(|(key, _)| key.to_string())
+            .collect();
+        for key in removed {
+            existing.remove(&key);
+        }
+    }
+
+    /// 只保留 `new` 中也有的键，避免合并时删除 `T` 未建模的键
+    fn retain_keys(old: &mut dyn TableLike, new: &dyn TableLike) {
+        let keys: Vec<String> = old.iter().map(|(key, _)| key.to_string()).collect();
+        for key in keys {
+            match (old.get_mut(&key), new.get(&key)) {
+                (Some(old_item), Some(new_item)) => {
+                    if let (Some(old_table), Some(new_table)) =
+                        (old_item.as_table_like_mut(), new_item.as_table_like())
+                    {
+                        retain_keys(old_table, new_table);
+                    }
+                }
+                _ => {
+                    old.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// 表和表数组逐项合并以保留其中的注释，无法逐项合并时返回 `false`
+    fn merge_nested(slot: &mut Item, old: &Item, new: &Item) -> bool {
+        if let (Some(old_table), Some(new_table)) = (old.as_table_like(), new.as_table_like()) {
+            let sections = slot.is_table();
+            if let Some(table) = slot.as_table_like_mut() {
+                merge_table(table, old_table, new_table, sections);
+                return true;
+            }
+            return false;
+        }
+        let (Some(old_list), Some(new_list), Some(tables)) = (
+            old.as_array(),
+            new.as_array(),
+            slot.as_array_of_tables_mut(),
+        ) else {
+            return false;
+        };
+        if old_list.len() != new_list.len() || tables.len() != new_list.len() {
+            return false;
+        }
+        for (index, table) in tables.iter_mut().enumerate() {
+            match (
+                old_list.get(index).and_then(Value::as_inline_table),
+                new_list.get(index).and_then(Value::as_inline_table),
+            ) {
+                (Some(old_table), Some(new_table)) => {
+                    merge_table(table, old_table, new_table, true)
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// 替换值并保留原有的前后缀空白与注释
+    fn replace_item(slot: &mut Item, new: Item) {
+        let sections = slot.is_table() || slot.is_array_of_tables();
+        match (slot.as_value_mut(), new) {
+            (Some(value), Item::Value(mut new_value)) => {
+                *new_value.decor_mut() = value.decor().clone();
+                *value = new_value;
+            }
+            (_, new) => *slot = if sections { into_section(new) } else { new },
+        }
+    }
+
+    /// 将内联表转换为独立表、内联表数组转换为表数组
+    fn into_section(item: Item) -> Item {
+        match item {
+            Item::Value(Value::InlineTable(inline)) => {
+                let mut table = inline.into_table();
+                for (_, child) in table.iter_mut() {
+                    *child = into_section(std::mem::take(child));
+                }
+                Item::Table(table)
+            }
+            Item::Value(Value::Array(list))
+                if !list.is_empty() && list.iter().all(Value::is_inline_table) =>
+            {
+                let mut tables = ArrayOfTables::new();
+                for value in list {
+                    if let Item::Table(table) = into_section(Item::Value(value)) {
+                        tables.push(table);
+                    }
+                }
+                Item::ArrayOfTables(tables)
+            }
+            other => other,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::TomlEditor;
+        use crate::error::ConfIOReason;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+        struct Server {
+            host: String,
+            port: u16,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            tls: Option<Tls>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            upstream: Vec<Upstream>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+        struct Tls {
+            cert: String,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+        struct Upstream {
+            name: String,
+            weight: u32,
+        }
+
+        const SERVER: &str = "\
+# 服务配置
+host = \"127.0.0.1\" # 监听地址
+port = 8080
+debug = true # 未建模的键
+
+# 上游节点
+[[upstream]]
+name = \"a\"
+weight = 1 # 主节点
+
+[[upstream]]
+name = \"b\"
+weight = 2
+";
+
+        #[test]
+        fn test_update_from_keeps_unmodeled_keys() {
+            let mut editor = TomlEditor::parse(SERVER).unwrap();
+            let mut server: Server = editor.load().unwrap();
+            server.port = 9090;
+            server.upstream[0].weight = 10;
+            editor.update_from(&server).unwrap();
+
+            assert_eq!(
+                editor.to_string(),
+                SERVER
+                    .replace("port = 8080", "port = 9090")
+                    .replace("weight = 1 #", "weight = 10 #")
+            );
+        }
+
+        #[test]
+        fn test_update_from_adds_and_removes_fields() {
+            let mut editor = TomlEditor::parse(SERVER).unwrap();
+            let mut server: Server = editor.load().unwrap();
+            server.upstream.clear();
+            server.tls = Some(Tls {
+                cert: "server.pem".to_string(),
+            });
+            editor.update_from(&server).unwrap();
+
+            let content = editor.to_string();
+            assert!(content.starts_with("# 服务配置\nhost = \"127.0.0.1\" # 监听地址\n"));
+            assert!(content.contains("debug = true # 未建模的键\n"));
+            assert!(content.contains("[tls]\ncert = \"server.pem\"\n"));
+            assert!(!content.contains("[[upstream]]"));
+            assert_eq!(editor.load::<Server>().unwrap(), server);
+        }
+
+        #[test]
+        fn test_update_from_repairs_unloadable_document() {
+            // 缺少必填字段 `port`，`weight` 类型不符，文档无法读取为 `Server`
+            let content = SERVER
+                .replace("port = 8080\n", "")
+                .replace("weight = 2", "weight = \"two\"");
+            let mut editor = TomlEditor::parse(&content).unwrap();
+            assert!(editor.load::<Server>().is_err());
+
+            let server = Server {
+                host: "127.0.0.1".to_string(),
+                port: 9090,
+                tls: None,
+                upstream: vec![
+                    Upstream {
+                        name: "a".to_string(),
+                        weight: 1,
+                    },
+                    Upstream {
+                        name: "b".to_string(),
+                        weight: 2,
+                    },
+                ],
+            };
+            editor.update_from(&server).unwrap();
+
+            let updated = editor.to_string();
+            assert!(updated.starts_with("# 服务配置\nhost = \"127.0.0.1\" # 监听地址\n"));
+            assert!(updated.contains("debug = true # 未建模的键\n"), "{updated}");
+            assert!(updated.contains("port = 9090\n"));
+            assert!(updated.contains("weight = 1 # 主节点\n"));
+            assert!(updated.contains("weight = 2\n"));
+            assert_eq!(editor.load::<Server>().unwrap(), server);
+        }
+
+        #[test]
+        fn test_set_and_remove_by_key_path() {
+            let mut editor = TomlEditor::parse(SERVER).unwrap();
+            editor.set("port", &9090).unwrap();
+            editor.set("tls.cert", "server.pem").unwrap();
+            assert!(editor.remove("debug").unwrap());
+            assert!(!editor.remove("missing.key").unwrap());
+
+            let content = editor.to_string();
+            assert!(content.contains("port = 9090\n"));
+            assert!(content.contains("[tls]\ncert = \"server.pem\"\n"));
+            assert!(!content.contains("debug"));
+            assert!(content.contains("weight = 1 # 主节点\n"));
+        }
+
+        #[test]
+        fn test_set_rejects_non_table_path() {
+            let mut editor = TomlEditor::parse(SERVER).unwrap();
+            assert!(editor.set("port.value", &1).is_err());
+            assert!(editor.set("a..b", &1).is_err());
+        }
+
+        #[test]
+        fn test_open_reports_parse_error() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("broken.toml");
+            std::fs::write(&path, "name = \"a\"\nport = = 3\n").unwrap();
+
+            let err = TomlEditor::open(&path).unwrap_err();
+            match err.reason() {
+                ConfIOReason::Parse { line, column, .. } => {
+                    assert_eq!(*line, Some(2));
+                    assert_eq!(*column, Some(8));
+                }
+                other => panic!("unexpected reason: {other:?}"),
+            }
+        }
+
+        #[test]
+        fn test_save_writes_back_to_source() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("server.toml");
+            std::fs::write(&path, SERVER).unwrap();
+
+            let mut editor = TomlEditor::open(&path).unwrap();
+            editor.set("host", "0.0.0.0").unwrap();
+            editor.save().unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert!(content.contains("host = \"0.0.0.0\" # 监听地址\n"));
+            assert!(TomlEditor::parse(SERVER).unwrap().save().is_err());
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+mod yaml_doc {
+    use orion_error::{ContextRecord, ErrorOwe, ErrorWith, OperationContext, ToStructError};
+    use saphyr_parser::{Event, Parser, ScalarStyle, ScanError, Span, StrInput};
+    use serde_yaml::{Mapping, Value};
+    use std::{
+        fmt::Display,
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use crate::diagnostic::parse_error;
+    use crate::error::{ConfIOReason, OrionConfResult};
+    use crate::format::{ConfFormat, yaml_from_str};
+    use crate::persist::save_to_file;
+
+    /// 按键路径修改 YAML 文本的编辑器
+    ///
+    /// 只替换目标值所在的字节区间，文件其余部分（注释、锚点、键顺序）逐字节保持不变。
+    #[derive(Clone, Debug)]
+    pub struct YamlEditor {
+        path: Option<PathBuf>,
+        content: String,
+    }
+
+    impl YamlEditor {
+        /// 读取已有文件
+        pub fn open(path: &Path) -> OrionConfResult<Self> {
+            let mut ctx = OperationContext::want("open yaml document").with_auto_log();
+            ctx.record("from path", path);
+            let content = fs::read_to_string(path).owe_res().with(&ctx)?;
+            let editor = Self {
+                path: Some(path.to_path_buf()),
+                content,
+            };
+            editor.root().with(&ctx)?;
+            ctx.mark_suc();
+            Ok(editor)
+        }
+
+        /// 解析内存中的 YAML 文本
+        pub fn parse(content: &str) -> OrionConfResult<Self> {
+            let editor = Self {
+                path: None,
+                content: content.to_string(),
+            };
+            editor.root()?;
+            Ok(editor)
+        }
+
+        /// 将当前文档反序列化为 `T`
+        pub fn load<T>(&self) -> OrionConfResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            yaml_from_str(&self.content)
+                .map_err(|e| parse_error(ConfFormat::Yaml, self.path.as_deref(), &self.content, e))
+        }
+
+        /// 设置点分键路径（如 `server.port`、`servers.0.host`）上的值
+        ///
+        /// 缺失的键追加到所在块映射的末尾；路径经过别名（`*alias` 或 `<<` 合并键）时报错。
+        pub fn set<V>(&mut self, key_path: &str, value: &V) -> OrionConfResult<()>
+        where
+            V: serde::Serialize + ?Sized,
+        {
+            let value = serde_yaml::to_value(value)
+                .map_err(|e| ConfIOReason::from(e.to_string()).to_err())?;
+            let segments: Vec<&str> = key_path.split('.').collect();
+            if segments.iter().any(|segment| segment.is_empty()) {
+                return Err(patch_error(key_path, "invalid key path"));
+            }
+            let root = self
+                .root()?
+                .ok_or_else(|| patch_error(key_path, "document is empty"))?;
+
+            let mut node = &root;
+            for (index, segment) in segments.iter().enumerate() {
+                let walked = segments[..index].join(".");
+                node = match &node.kind {
+                    Kind::Alias => {
+                        return Err(patch_error(key_path, &format!("`{walked}` is an alias")));
+                    }
+                    Kind::Scalar => {
+                        return Err(patch_error(
+                            key_path,
+                            &format!("`{walked}` is not a mapping or sequence"),
+                        ));
+                    }
+                    Kind::Sequence(items) => segment
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| items.get(index))
+                        .ok_or_else(|| {
+                            patch_error(key_path, &format!("no item `{segment}` in `{walked}`"))
+                        })?,
+                    Kind::Mapping(entries) => match lookup(entries, segment) {
+                        Some(child) => child,
+                        None if lookup(entries, "<<").is_some() => {
+                            return Err(patch_error(
+                                key_path,
+                                &format!("`{walked}` merges keys from an alias"),
+                            ));
+                        }
+                        None => return self.insert(key_path, node, &segments[index..], value),
+                    },
+                };
+            }
+            if matches!(node.kind, Kind::Alias) {
+                return Err(patch_error(key_path, "target is an alias"));
+            }
+            let text = render(node, &value)?.replace('\n', self.line_ending());
+            self.content.replace_range(node.start..node.end, &text);
+            Ok(())
+        }
+
+        /// 将 `value` 在各键路径上的取值写入文档
+        pub fn patch_from<T>(&mut self, value: &T, key_paths: &[&str]) -> OrionConfResult<()>
+        where
+            T: serde::Serialize,
+        {
+            let tree = serde_yaml::to_value(value)
+                .map_err(|e| ConfIOReason::from(e.to_string()).to_err())?;
+            for key_path in key_paths {
+                let field = key_path
+                    .split('.')
+                    .try_fold(&tree, |node, segment| match node {
+                        Value::Sequence(items) => items.get(segment.parse::<usize>().ok()?),
+                        _ => node.get(segment),
+                    })
+                    .ok_or_else(|| patch_error(key_path, "not found in value"))?;
+                self.set(key_path, field)?;
+            }
+            Ok(())
+        }
+
+        /// 写回打开时的文件
+        pub fn save(&self) -> OrionConfResult<()> {
+            let path = self.path.as_deref().ok_or_else(|| {
+                ConfIOReason::from("yaml document has no source path".to_string()).to_err()
+            })?;
+            self.save_to(path)
+        }
+
+        /// 原子写入指定文件
+        pub fn save_to(&self, path: &Path) -> OrionConfResult<()> {
+            save_to_file(path, "yaml document", || Ok(self.content.clone()))
+        }
+
+        /// 解析第一个文档的节点结构
+        fn root(&self) -> OrionConfResult<Option<Node>> {
+            Reader::new(&self.content)
+                .document()
+                .map_err(|e| parse_error(ConfFormat::Yaml, self.path.as_deref(), &self.content, e))
+        }
+
+        /// 在块映射末尾追加缺失的键
+        fn insert(
+            &mut self,
+            key_path: &str,
+            mapping: &Node,
+            segments: &[&str],
+            value: Value,
+        ) -> OrionConfResult<()> {
+            if mapping.flow {
+                return Err(patch_error(key_path, "cannot add keys to a flow mapping"));
+            }
+            let entry = segments.iter().rev().fold(value, |value, segment| {
+                let mut map = Mapping::new();
+                map.insert(Value::from(*segment), value);
+                Value::Mapping(map)
+            });
+            let text = to_yaml(&entry)?;
+            // 插在最后一个值所在行的换行符之前，使该行的行尾注释留在原处
+            let at = self.content[mapping.end..]
+                .find('\n')
+                .map_or(self.content.len(), |offset| mapping.end + offset);
+            let at = if self.content[..at].ends_with('\r') {
+                at - 1
+            } else {
+                at
+            };
+            let eol = self.line_ending();
+            let text = format!("\n{}", indent(&text, mapping.column, true)).replace('\n', eol);
+            self.content.insert_str(at, &text);
+            Ok(())
+        }
+
+        /// 文档使用的换行符，插入的文本与之保持一致
+        fn line_ending(&self) -> &'static str {
+            if self.content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            }
+        }
+    }
+
+    impl Display for YamlEditor {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.content)
+        }
+    }
+
+    fn patch_error(key_path: &str, reason: &str) -> orion_error::StructError<ConfIOReason> {
+        ConfIOReason::from(format!("cannot patch yaml key path `{key_path}`: {reason}")).to_err()
+    }
+
+    fn lookup<'a>(entries: &'a [(Option<String>, Node)], key: &str) -> Option<&'a Node> {
+        entries
+            .iter()
+            .find(|(name, _)| name.as_deref() == Some(key))
+            .map(|(_, node)| node)
+    }
+
+    fn to_yaml(value: &Value) -> OrionConfResult<String> {
+        serde_yaml::to_string(value)
+            .map(|text| text.trim_end().to_string())
+            .map_err(|e| ConfIOReason::from(e.to_string()).to_err())
+    }
+
+    fn to_flow(value: &Value) -> OrionConfResult<String> {
+        // JSON 是合法的 YAML 流式写法
+        serde_json::to_string(value).map_err(|e| ConfIOReason::from(e.to_string()).to_err())
+    }
+
+    /// 除首行外按列缩进
+    fn indent(text: &str, column: usize, first: bool) -> String {
+        let pad = " ".repeat(column);
+        text.split('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                if line.is_empty() || (index == 0 && !first) {
+                    line.to_string()
+                } else {
+                    format!("{pad}{line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 渲染替换文本：块集合保持块写法，其余位置使用单行写法
+    fn render(node: &Node, value: &Value) -> OrionConfResult<String> {
+        let block = matches!(node.kind, Kind::Mapping(_) | Kind::Sequence(_)) && !node.flow;
+        let text = match value {
+            Value::Mapping(map) if block && !map.is_empty() => {
+                return Ok(indent(&to_yaml(value)?, node.column, false));
+            }
+            Value::Sequence(items) if block && !items.is_empty() => {
+                return Ok(indent(&to_yaml(value)?, node.column, false));
+            }
+            Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_) => to_flow(value)?,
+            _ => match to_yaml(value)? {
+                // 多行字符串会渲染成块标量
+                text if text.contains('\n') => to_flow(value)?,
+                text => text,
+            },
+        };
+        // 空值没有占位文本，需要与 `:` 隔开
+        Ok(if node.start == node.end {
+            format!(" {text}")
+        } else {
+            text
+        })
+    }
+
+    #[derive(Debug)]
+    struct Node {
+        kind: Kind,
+        flow: bool,
+        /// 字节区间，不含其后的注释与空行
+        start: usize,
+        end: usize,
+        column: usize,
+    }
+
+    #[derive(Debug)]
+    enum Kind {
+        Scalar,
+        Alias,
+        Mapping(Vec<(Option<String>, Node)>),
+        Sequence(Vec<Node>),
+    }
+
+    /// 由解析事件构造带字节区间的节点树
+    struct Reader<'a> {
+        content: &'a str,
+        parser: Parser<'a, StrInput<'a>>,
+        /// 字符下标到字节偏移
+        offsets: Vec<usize>,
+        /// 已读节点的结束位置
+        last_end: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn new(content: &'a str) -> Self {
+            let offsets = content
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(content.len()))
+                .collect();
+            Self {
+                content,
+                parser: Parser::new_from_str(content),
+                offsets,
+                last_end: 0,
+            }
+        }
+
+        fn next(&mut self) -> Result<(Event<'a>, Span), ScanError> {
+            match self.parser.next_event() {
+                Some(event) => event,
+                None => Ok((Event::StreamEnd, Span::default())),
+            }
+        }
+
+        fn offset(&self, index: usize) -> usize {
+            self.offsets[index.min(self.offsets.len() - 1)]
+        }
+
+        fn document(&mut self) -> Result<Option<Node>, ScanError> {
+            loop {
+                let (event, span) = self.next()?;
+                match event {
+                    Event::StreamStart | Event::DocumentStart(_) => {}
+                    Event::StreamEnd | Event::DocumentEnd => return Ok(None),
+                    event => return self.node(event, span).map(Some),
+                }
+            }
+        }
+
+        fn node(&mut self, event: Event<'a>, span: Span) -> Result<Node, ScanError> {
+            let mut start = self.offset(span.start.index());
+            let mut column = span.start.col();
+            let node = match event {
+                Event::Scalar(_, style, ..) => {
+                    let (start, end) =
+                        self.scalar_range(style, start, self.offset(span.end.index()));
+                    Node {
+                        kind: Kind::Scalar,
+                        flow: false,
+                        start,
+                        end,
+                        column,
+                    }
+                }
+                Event::Alias(_) => Node {
+                    kind: Kind::Alias,
+                    flow: false,
+                    start,
+                    end: self.offset(span.end.index()),
+                    column,
+                },
+                Event::MappingStart(..) => {
+                    let flow = span.end.index() > span.start.index();
+                    let mut entries = Vec::new();
+                    loop {
+                        let (event, span) = self.next()?;
+                        if let Event::MappingEnd = event {
+                            self.close(flow, &span);
+                            break;
+                        }
+                        let key = match &event {
+                            Event::Scalar(name, ..) => Some(name.to_string()),
+                            _ => None,
+                        };
+                        self.node(event, span)?;
+                        let (event, span) = self.next()?;
+                        let mut value = self.node(event, span)?;
+                        if value.start == value.end {
+                            // 空值的位置紧跟在 `:` 之后
+                            let colon = self.content[self.last_end..].find(':');
+                            value.start = colon.map_or(self.last_end, |i| self.last_end + i + 1);
+                            value.end = value.start;
+                            self.last_end = value.end;
+                        }
+                        entries.push((key, value));
+                    }
+                    Node {
+                        kind: Kind::Mapping(entries),
+                        flow,
+                        start,
+                        end: self.last_end.max(start),
+                        column,
+                    }
+                }
+                Event::SequenceStart(..) => {
+                    let flow = span.end.index() > span.start.index();
+                    if !flow {
+                        // 块序列的起点从首项内容前移到 `-`
+                        let before = self.content[..start].trim_end_matches(' ');
+                        if let Some(dash) = before.strip_suffix('-') {
+                            column -= start - dash.len();
+                            start = dash.len();
+                        }
+                    }
+                    let mut items = Vec::new();
+                    loop {
+                        let (event, span) = self.next()?;
+                        if let Event::SequenceEnd = event {
+                            self.close(flow, &span);
+                            break;
+                        }
+                        items.push(self.node(event, span)?);
+                    }
+                    Node {
+                        kind: Kind::Sequence(items),
+                        flow,
+                        start,
+                        end: self.last_end.max(start),
+                        column,
+                    }
+                }
+                _ => Node {
+                    kind: Kind::Scalar,
+                    flow: false,
+                    start,
+                    end: start,
+                    column,
+                },
+            };
+            self.last_end = self.last_end.max(node.end);
+            Ok(node)
+        }
+
+        /// 流式集合的结束位置在闭合括号之后；块集合结束于最后一个值，不含其后的注释
+        fn close(&mut self, flow: bool, span: &Span) {
+            if flow {
+                self.last_end = self.offset(span.end.index());
+            }
+        }
+
+        /// 修正标量区间：引号标量截到闭合引号，块标量从 `|` / `>` 开始并去掉结尾空白
+        fn scalar_range(&self, style: ScalarStyle, start: usize, end: usize) -> (usize, usize) {
+            let bytes = self.content.as_bytes();
+            match style {
+                ScalarStyle::DoubleQuoted => {
+                    let mut index = start + 1;
+                    while index < bytes.len() {
+                        match bytes[index] {
+                            b'\\' => index += 2,
+                            b'"' => return (start, index + 1),
+                            _ => index += 1,
+                        }
+                    }
+                    (start, end)
+                }
+                ScalarStyle::SingleQuoted => {
+                    let mut index = start + 1;
+                    while index < bytes.len() {
+                        match (bytes[index], bytes.get(index + 1)) {
+                            (b'\'', Some(b'\'')) => index += 2,
+                            (b'\'', _) => return (start, index + 1),
+                            _ => index += 1,
+                        }
+                    }
+                    (start, end)
+                }
+                ScalarStyle::Literal | ScalarStyle::Folded => {
+                    let indicator = self.content[self.last_end..start]
+                        .find(['|', '>'])
+                        .map_or(start, |offset| self.last_end + offset);
+                    let end = indicator + self.content[indicator..end].trim_end().len();
+                    (indicator, end)
+                }
+                ScalarStyle::Plain => (start, end),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::YamlEditor;
+        use crate::error::ConfIOReason;
+        use serde::Serialize;
+
+        const SERVICE: &str = "\
+# 服务配置
+defaults: &defaults
+  timeout: 30 # 秒
+  retries: 3
+
+server:
+  host: \"127.0.0.1\" # 监听地址
+  port: 8080
+  tags: [web, api]
+  upstream:
+  - name: a
+    weight: 1
+  - name: b
+    weight: 2
+  banner: |
+    hello
+    world
+
+worker:
+  <<: *defaults
+  queue: jobs
+primary: *defaults
+";
+
+        fn set<V: Serialize + ?Sized>(key_path: &str, value: &V) -> String {
+            let mut editor = YamlEditor::parse(SERVICE).unwrap();
+            editor.set(key_path, value).unwrap();
+            editor.to_string()
+        }
+
+        #[test]
+        fn test_set_scalar_keeps_rest_of_file() {
+            assert_eq!(
+                set("server.port", &9090),
+                SERVICE.replace("port: 8080", "port: 9090")
+            );
+            assert_eq!(
+                set("server.host", "0.0.0.0"),
+                SERVICE.replace("\"127.0.0.1\"", "0.0.0.0")
+            );
+            assert_eq!(
+                set("defaults.timeout", &60),
+                SERVICE.replace("timeout: 30", "timeout: 60")
+            );
+            assert_eq!(
+                set("server.upstream.1.weight", &5),
+                SERVICE.replace("weight: 2", "weight: 5")
+            );
+            assert_eq!(
+                set("server.banner", "bye"),
+                SERVICE.replace("|\n    hello\n    world", "bye")
+            );
+        }
+
+        #[test]
+        fn test_set_collections() {
+            assert_eq!(
+                set("server.tags", &["web"]),
+                SERVICE.replace("[web, api]", "[\"web\"]")
+            );
+            assert_eq!(
+                set(
+                    "server.upstream",
+                    &[std::collections::BTreeMap::from([("name", "c")])],
+                ),
+                SERVICE.replace(
+                    "- name: a\n    weight: 1\n  - name: b\n    weight: 2",
+                    "- name: c"
+                )
+            );
+        }
+
+        #[test]
+        fn test_set_inserts_missing_keys() {
+            assert_eq!(
+                set("server.tls.cert", "server.pem"),
+                SERVICE.replace("    world\n", "    world\n  tls:\n    cert: server.pem\n")
+            );
+            assert_eq!(
+                set("defaults.backoff", &5),
+                SERVICE.replace("retries: 3\n", "retries: 3\n  backoff: 5\n")
+            );
+        }
+
+        #[test]
+        fn test_set_rejects_alias() {
+            let mut editor = YamlEditor::parse(SERVICE).unwrap();
+            for key_path in ["primary.timeout", "primary", "worker.timeout"] {
+                let err = editor.set(key_path, &1).unwrap_err();
+                assert!(
+                    err.reason().to_string().contains("alias"),
+                    "{key_path}: {err}"
+                );
+            }
+            // 合并键之外的键可以直接修改
+            editor.set("worker.queue", "mail").unwrap();
+            assert_eq!(editor.to_string(), SERVICE.replace("jobs", "mail"));
+        }
+
+        #[test]
+        fn test_set_rejects_invalid_paths() {
+            let mut editor = YamlEditor::parse(SERVICE).unwrap();
+            assert!(editor.set("server.port.value", &1).is_err());
+            assert!(editor.set("server.upstream.5", &1).is_err());
+            assert!(editor.set("server.tags.x", &1).is_err());
+            assert!(editor.set("server..port", &1).is_err());
+            assert_eq!(editor.to_string(), SERVICE);
+        }
+
+        #[test]
+        fn test_set_empty_value() {
+            let mut editor = YamlEditor::parse("name:\nport: 1\n").unwrap();
+            editor.set("name", "app").unwrap();
+            assert_eq!(editor.to_string(), "name: app\nport: 1\n");
+        }
+
+        #[test]
+        fn test_set_keeps_crlf_line_endings() {
+            let mut editor = YamlEditor::parse("a: 1\r\nb: 2 # keep\r\n").unwrap();
+            editor.set("c", &3).unwrap();
+            editor.set("d.e", &4).unwrap();
+            editor.set("a", &[1, 2]).unwrap();
+            assert_eq!(
+                editor.to_string(),
+                "a: [1,2]\r\nb: 2 # keep\r\nc: 3\r\nd:\r\n  e: 4\r\n"
+            );
+
+            let mut editor = YamlEditor::parse("m:\r\n  x: 1\r\nn: 0\r\n").unwrap();
+            editor
+                .set("m", &std::collections::BTreeMap::from([("y", 1), ("z", 2)]))
+                .unwrap();
+            assert_eq!(editor.to_string(), "m:\r\n  y: 1\r\n  z: 2\r\nn: 0\r\n");
+        }
+
+        #[test]
+        fn test_parse_reports_location() {
+            let err = YamlEditor::parse("name: a\nport: [1\n").unwrap_err();
+            match err.reason() {
+                ConfIOReason::Parse { line, .. } => assert!(line.is_some()),
+                other => panic!("unexpected reason: {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,566 @@
+use orion_error::ToStructError;
+use orion_variate::EnvDict;
+use serde_json::{Map, Value};
+
+use crate::error::{ConfIOReason, OrionConfResult};
+
+/// 未解析的 `${VAR}` 占位符的处理策略
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnresolvedPolicy {
+    /// 保留占位符原文，不提示
+    Ignore,
+    /// 保留占位符原文，输出警告
+    #[default]
+    Warn,
+    /// 返回 [`ConfIOReason::UnresolvedVars`] 错误
+    Error,
+}
+
+/// `${VAR}` 占位符的替换方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubstitutionMode {
+    /// 解析前对整个文本做替换，变量值可以是数字、布尔值甚至片段
+    #[default]
+    Text,
+    /// 解析后只替换值树中的字符串叶子，变量值不会改变文档结构；
+    /// 占位符必须位于字符串位置，数字和布尔字段在反序列化时按目标类型转换
+    Structured,
+}
+
+/// 环境变量加载选项，供 `env_load_*_with` / `env_parse_*_with` 按次调用时指定
+#[derive(Clone, Debug, Default)]
+pub struct EnvOptions {
+    overlay: Option<EnvOverlay>,
+    unresolved: UnresolvedPolicy,
+    substitution: SubstitutionMode,
+}
+
+impl EnvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 严格模式：存在未解析的占位符时报错
+    pub fn strict() -> Self {
+        Self::default().with_unresolved(UnresolvedPolicy::Error)
+    }
+
+    pub fn with_unresolved(mut self, policy: UnresolvedPolicy) -> Self {
+        self.unresolved = policy;
+        self
+    }
+
+    pub fn unresolved(&self) -> UnresolvedPolicy {
+        self.unresolved
+    }
+
+    pub fn with_substitution(mut self, mode: SubstitutionMode) -> Self {
+        self.substitution = mode;
+        self
+    }
+
+    pub fn substitution(&self) -> SubstitutionMode {
+        self.substitution
+    }
+
+    /// 在文件解析之后、反序列化之前应用环境变量覆盖
+    pub fn with_overlay(mut self, overlay: EnvOverlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    pub fn overlay(&self) -> Option<&EnvOverlay> {
+        self.overlay.as_ref()
+    }
+}
+
+/// 12-factor 风格的环境变量覆盖
+///
+/// 将 `APP__DATABASE__URL` 这样的变量映射到配置树的 `database.url`：
+/// 去掉前缀和分隔符后按分隔符拆分路径，键名大小写不敏感地匹配已有键，新键使用小写。
+/// 变量值以字符串写入配置树，反序列化时再按目标字段类型转换为数字或布尔值。
+#[derive(Clone, Debug)]
+pub struct EnvOverlay {
+    prefix: String,
+    separator: String,
+    vars: Option<Vec<(String, String)>>,
+}
+
+impl EnvOverlay {
+    /// 以 `prefix` 为前缀、`__` 为分隔符读取进程环境变量
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: "__".to_string(),
+            vars: None,
+        }
+    }
+
+    pub fn with_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// 使用给定的变量代替进程环境变量
+    pub fn with_vars<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.vars = Some(
+            vars.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// 变量名对应的配置键路径，不匹配前缀时返回 `None`
+    pub fn key_path(&self, name: &str) -> Option<Vec<String>> {
+        let head = format!("{}{}", self.prefix, self.separator);
+        let (name_head, rest) = (name.get(..head.len())?, name.get(head.len()..)?);
+        if !name_head.eq_ignore_ascii_case(&head) || rest.is_empty() {
+            return None;
+        }
+        let segments: Vec<String> = rest
+            .split(self.separator.as_str())
+            .map(|segment| segment.to_ascii_lowercase())
+            .collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return None;
+        }
+        Some(segments)
+    }
+
+    /// 将匹配的环境变量写入配置树
+    pub fn apply(&self, tree: &mut Value) {
+        let mut vars: Vec<(String, String)> = match &self.vars {
+            Some(vars) => vars.clone(),
+            None => std::env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+                .collect(),
+        };
+        // 排序保证结果与环境变量的枚举顺序无关
+        vars.sort();
+        for (name, value) in vars {
+            if let Some(path) = self.key_path(&name) {
+                set_path(tree, &path, Value::String(value));
+            }
+        }
+    }
+}
+
+/// 替换文本中的 `${VAR}` 占位符，并按策略处理未解析的变量
+///
+/// 支持 shell 风格的修饰符：
+/// - `${VAR:-default}`：变量未设置或为空时使用默认值
+/// - `${VAR-default}`：变量未设置时使用默认值
+/// - `${VAR:?message}`：变量未设置或为空时返回 [`ConfIOReason::RequiredVar`]
+/// - `${VAR?message}`：变量未设置时返回 [`ConfIOReason::RequiredVar`]
+///
+/// 默认值和提示信息中可以嵌套占位符；兼容原有的 `${VAR:default}` 写法。
+pub(crate) fn substitute(
+    content: &str,
+    dict: &EnvDict,
+    policy: UnresolvedPolicy,
+) -> OrionConfResult<String> {
+    let mut unresolved = Vec::new();
+    let evaluated = expand(content, Vars::with_process_env(dict), &mut unresolved)?;
+    check_unresolved(unresolved, policy)?;
+    Ok(evaluated)
+}
+
+/// 只替换值树字符串叶子中的 `${VAR}` 占位符，并按策略处理未解析的变量
+pub(crate) fn substitute_leaves(
+    tree: &mut Value,
+    dict: &EnvDict,
+    policy: UnresolvedPolicy,
+) -> OrionConfResult<()> {
+    let mut unresolved = Vec::new();
+    substitute_value(tree, Vars::with_process_env(dict), &mut unresolved)?;
+    check_unresolved(unresolved, policy)
+}
+
+fn substitute_value(
+    value: &mut Value,
+    vars: Vars<'_>,
+    unresolved: &mut Vec<String>,
+) -> OrionConfResult<()> {
+    match value {
+        Value::String(text) if text.contains("${") => {
+            *text = expand(text, vars, unresolved)?;
+        }
+        Value::Array(list) => {
+            for item in list {
+                substitute_value(item, vars, unresolved)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                substitute_value(item, vars, unresolved)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 占位符的取值来源：字典，以及（可选）作为后备的进程环境变量
+#[derive(Clone, Copy)]
+pub(crate) struct Vars<'a> {
+    dict: &'a EnvDict,
+    process_env: bool,
+}
+
+impl<'a> Vars<'a> {
+    /// 只在字典中查找
+    #[cfg_attr(not(feature = "dotenv"), allow(dead_code))]
+    pub(crate) fn dict_only(dict: &'a EnvDict) -> Self {
+        Self {
+            dict,
+            process_env: false,
+        }
+    }
+
+    /// 字典中没有时查找进程环境变量
+    pub(crate) fn with_process_env(dict: &'a EnvDict) -> Self {
+        Self {
+            dict,
+            process_env: true,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        // EnvDict 的键统一为大写
+        self.dict
+            .get_case_insensitive(name)
+            .map(|value| value.to_string())
+            .or_else(|| self.process_env.then(|| std::env::var(name).ok()).flatten())
+    }
+}
+
+/// 展开文本中的占位符，未解析的 `${VAR}` 原样保留并记录变量名
+pub(crate) fn expand(
+    input: &str,
+    vars: Vars<'_>,
+    unresolved: &mut Vec<String>,
+) -> OrionConfResult<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let body_start = start + 2;
+        let Some(len) = closing_brace(&rest[body_start..]) else {
+            // 未闭合的占位符原样保留
+            out.push_str(&rest[start..]);
+            return Ok(out);
+        };
+        out.push_str(&expand_placeholder(
+            &rest[body_start..body_start + len],
+            vars,
+            unresolved,
+        )?);
+        rest = &rest[body_start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// 找到与 `${` 匹配的 `}`，跳过嵌套的占位符
+fn closing_brace(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'$' if bytes.get(index + 1) == Some(&b'{') => {
+                depth += 1;
+                index += 1;
+            }
+            b'}' if depth == 0 => return Some(index),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+fn expand_placeholder(
+    body: &str,
+    vars: Vars<'_>,
+    unresolved: &mut Vec<String>,
+) -> OrionConfResult<String> {
+    let (name, modifier) = body.split_at(body.find([':', '-', '?']).unwrap_or(body.len()));
+    if name.is_empty() {
+        return Ok(format!("${{{body}}}"));
+    }
+    let value = vars.get(name);
+    let non_empty = value.clone().filter(|value| !value.is_empty());
+    let required = |message: &str, unresolved: &mut Vec<String>| {
+        let mut message = expand(message, vars, unresolved)?;
+        if message.is_empty() {
+            message = "parameter null or not set".to_string();
+        }
+        Err(ConfIOReason::RequiredVar {
+            name: name.to_string(),
+            message,
+        }
+        .to_err())
+    };
+
+    if modifier.is_empty() {
+        return Ok(value.unwrap_or_else(|| {
+            unresolved.push(name.to_string());
+            format!("${{{body}}}")
+        }));
+    }
+    if let Some(default) = modifier.strip_prefix(":-") {
+        return match non_empty {
+            Some(value) => Ok(value),
+            None => expand(default, vars, unresolved),
+        };
+    }
+    if let Some(message) = modifier.strip_prefix(":?") {
+        return match non_empty {
+            Some(value) => Ok(value),
+            None => required(message, unresolved),
+        };
+    }
+    if let Some(message) = modifier.strip_prefix('?') {
+        return match value {
+            Some(value) => Ok(value),
+            None => required(message, unresolved),
+        };
+    }
+    // `${VAR-default}` 以及兼容的 `${VAR:default}`
+    let default = modifier
+        .strip_prefix('-')
+        .or_else(|| modifier.strip_prefix(':'))
+        .unwrap_or(modifier);
+    match value {
+        Some(value) => Ok(value),
+        None => expand(default, vars, unresolved),
+    }
+}
+
+pub(crate) fn check_unresolved(
+    mut vars: Vec<String>,
+    policy: UnresolvedPolicy,
+) -> OrionConfResult<()> {
+    let mut seen = std::collections::HashSet::new();
+    vars.retain(|var| seen.insert(var.clone()));
+    if vars.is_empty() {
+        return Ok(());
+    }
+    match policy {
+        UnresolvedPolicy::Ignore => Ok(()),
+        UnresolvedPolicy::Warn => {
+            let msg = format!("vars not value : {}", vars.join(","));
+            eprintln!("{}", msg);
+            log::warn!(target: "conf", "{}", msg);
+            Ok(())
+        }
+        UnresolvedPolicy::Error => Err(ConfIOReason::UnresolvedVars(vars).to_err()),
+    }
+}
+
+/// 按路径写入值，中间节点不是映射时替换为映射
+fn set_path(tree: &mut Value, path: &[String], value: Value) {
+    let Some((head, rest)) = path.split_first() else {
+        *tree = value;
+        return;
+    };
+    if !tree.is_object() {
+        *tree = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = tree {
+        let key = map
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case(head))
+            .cloned()
+            .unwrap_or_else(|| head.clone());
+        let child = map.entry(key).or_insert(Value::Null);
+        set_path(child, rest, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_variate::ValueType;
+    use serde_json::json;
+
+    #[test]
+    fn test_substitute_policies() {
+        let mut dict = EnvDict::new();
+        dict.insert("KNOWN", ValueType::from("v"));
+        let content = "a=${KNOWN} b=${ORION_CONF_MISSING_A} c=${ORION_CONF_MISSING_B} d=${ORION_CONF_MISSING_A}";
+
+        let ignored = substitute(content, &dict, UnresolvedPolicy::Ignore).unwrap();
+        assert!(ignored.starts_with("a=v b=${ORION_CONF_MISSING_A}"));
+        let warned = substitute(content, &dict, UnresolvedPolicy::Warn).unwrap();
+        assert_eq!(warned, ignored);
+
+        let err = substitute(content, &dict, UnresolvedPolicy::Error).unwrap_err();
+        assert_eq!(
+            err.reason(),
+            &ConfIOReason::UnresolvedVars(vec![
+                "ORION_CONF_MISSING_A".to_string(),
+                "ORION_CONF_MISSING_B".to_string()
+            ])
+        );
+        assert_eq!(
+            substitute("a=${KNOWN}", &dict, UnresolvedPolicy::Error).unwrap(),
+            "a=v"
+        );
+    }
+
+    #[test]
+    fn test_substitute_modifiers() {
+        let mut dict = EnvDict::new();
+        dict.insert("SET", ValueType::from("v"));
+        dict.insert("EMPTY", ValueType::from(""));
+        let cases = [
+            ("${SET:-d}", "v"),
+            ("${EMPTY:-d}", "d"),
+            ("${ORION_CONF_MISSING_C:-d}", "d"),
+            ("${SET-d}", "v"),
+            ("${EMPTY-d}", ""),
+            ("${ORION_CONF_MISSING_C-d}", "d"),
+            ("${ORION_CONF_MISSING_C-}", ""),
+            ("${ORION_CONF_MISSING_C:-a:b-c}", "a:b-c"),
+            ("${ORION_CONF_MISSING_C:-${SET}-x}", "v-x"),
+            ("${SET:?must be set}", "v"),
+            ("${EMPTY?must be set}", ""),
+            ("${ORION_CONF_MISSING_C:legacy}", "legacy"),
+            ("${}", "${}"),
+            ("tail ${SET", "tail ${SET"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(
+                substitute(content, &dict, UnresolvedPolicy::Error).unwrap(),
+                expected,
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_substitute_required() {
+        let mut dict = EnvDict::new();
+        dict.insert("EMPTY", ValueType::from(""));
+        dict.insert("HINT", ValueType::from("db url"));
+        let cases = [
+            (
+                "${ORION_CONF_MISSING_D:?need ${HINT}}",
+                "ORION_CONF_MISSING_D",
+                "need db url",
+            ),
+            ("${EMPTY:?}", "EMPTY", "parameter null or not set"),
+            (
+                "${ORION_CONF_MISSING_D?unset}",
+                "ORION_CONF_MISSING_D",
+                "unset",
+            ),
+        ];
+        for (content, name, message) in cases {
+            let err = substitute(content, &dict, UnresolvedPolicy::Ignore).unwrap_err();
+            assert_eq!(
+                err.reason(),
+                &ConfIOReason::RequiredVar {
+                    name: name.to_string(),
+                    message: message.to_string()
+                }
+            );
+        }
+
+        // 未使用的默认值不会被展开
+        assert_eq!(
+            substitute(
+                "${HINT:-${ORION_CONF_MISSING_D:?x}}",
+                &dict,
+                UnresolvedPolicy::Error
+            )
+            .unwrap(),
+            "db url"
+        );
+    }
+
+    #[test]
+    fn test_substitute_leaves() {
+        let mut dict = EnvDict::new();
+        dict.insert("QUOTED", ValueType::from("say \"hi\"\nbye"));
+        let mut tree = json!({
+            "${QUOTED}": "${QUOTED}",
+            "list": ["x-${QUOTED}", 1, null],
+            "nested": {"missing": "${ORION_CONF_MISSING_LEAF}"}
+        });
+        let err = substitute_leaves(&mut tree, &dict, UnresolvedPolicy::Error).unwrap_err();
+        assert_eq!(
+            err.reason(),
+            &ConfIOReason::UnresolvedVars(vec!["ORION_CONF_MISSING_LEAF".to_string()])
+        );
+        // 键名不参与替换
+        assert_eq!(tree["${QUOTED}"], json!("say \"hi\"\nbye"));
+        assert_eq!(tree["list"], json!(["x-say \"hi\"\nbye", 1, null]));
+    }
+
+    #[test]
+    fn test_key_path() {
+        let overlay = EnvOverlay::new("APP");
+        assert_eq!(
+            overlay.key_path("APP__DATABASE__URL"),
+            Some(vec!["database".to_string(), "url".to_string()])
+        );
+        assert_eq!(
+            overlay.key_path("app__NAME"),
+            Some(vec!["name".to_string()])
+        );
+        assert_eq!(overlay.key_path("APP_NAME"), None);
+        assert_eq!(overlay.key_path("OTHER__NAME"), None);
+        assert_eq!(overlay.key_path("APP__"), None);
+        assert_eq!(overlay.key_path("APP__A____B"), None);
+
+        let overlay = EnvOverlay::new("SVC").with_separator("_");
+        assert_eq!(
+            overlay.key_path("SVC_DB_HOST"),
+            Some(vec!["db".to_string(), "host".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_apply_overlay() {
+        let mut tree = json!({"name": "base", "Database": {"url": "pg://local", "pool": 4}});
+        EnvOverlay::new("APP")
+            .with_vars([
+                ("APP__DATABASE__URL", "pg://prod"),
+                ("APP__CACHE__TTL", "60"),
+                ("APP__NAME", "prod_app"),
+                ("PATH", "/usr/bin"),
+            ])
+            .apply(&mut tree);
+        assert_eq!(
+            tree,
+            json!({
+                "name": "prod_app",
+                "Database": {"url": "pg://prod", "pool": 4},
+                "cache": {"ttl": "60"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_overlay_with_custom_separator() {
+        let mut tree = json!({"level": "info", "db": {"host": "localhost"}});
+        EnvOverlay::new("SVC")
+            .with_separator("_")
+            .with_vars([("SVC_LEVEL", "debug"), ("SVC_DB_HOST", "db.internal")])
+            .apply(&mut tree);
+        assert_eq!(
+            tree,
+            json!({"level": "debug", "db": {"host": "db.internal"}})
+        );
+    }
+}
@@ -3,8 +3,13 @@ pub use orion_error::UvsFrom as UvsConfFrom;
 use orion_error::{ErrorCode, UvsReason};
 pub use orion_error::{ErrorOwe, ErrorWith, StructError, UvsFrom};
 pub use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::diagnostic::render_parse;
+use crate::format::ConfFormat;
+use crate::validate::{Violation, render_violations};
+
 #[derive(Clone, Debug, Serialize, PartialEq, Error)]
 pub enum ConfIOReason {
     // Preferred variant for arbitrary messages
@@ -13,8 +18,32 @@ pub enum ConfIOReason {
 
     #[error("{0}")]
     Uvs(UvsReason),
-    #[error("no format feature enabled - please enable at least one of: yaml, toml, json, ini")]
+    #[error(
+        "no format feature enabled - please enable at least one of: yaml, toml, json, json5, ini, ron, dotenv, hcl, properties, msgpack, cbor"
+    )]
     NoFormatEnabled,
+    #[error("format `{0}` not enabled - please enable the `{0}` feature")]
+    FormatNotEnabled(ConfFormat),
+    #[error("format `{0}` is binary and cannot be parsed as text or substituted with env vars")]
+    BinaryFormat(ConfFormat),
+    #[error("unresolved env vars: {}", .0.join(","))]
+    UnresolvedVars(Vec<String>),
+    #[error("required env var `{name}` not set: {message}")]
+    RequiredVar { name: String, message: String },
+    /// 配置内容解析或反序列化失败，位置信息可用时渲染出错行
+    #[error("{}", render_parse(.format, .path, .line, .column, .key_path, .message, .source_line))]
+    Parse {
+        format: ConfFormat,
+        path: Option<PathBuf>,
+        line: Option<usize>,
+        column: Option<usize>,
+        key_path: Option<String>,
+        message: String,
+        source_line: Option<String>,
+    },
+    /// 校验未通过，包含全部违规项
+    #[error("{}", render_violations(.0))]
+    Validation(Vec<Violation>),
 }
 
 // Keep legacy alias for compatibility
@@ -26,6 +55,12 @@ impl ErrorCode for ConfIOReason {
             ConfIOReason::Other(_) => 500,
             ConfIOReason::Uvs(r) => r.error_code(),
             ConfIOReason::NoFormatEnabled => 501,
+            ConfIOReason::FormatNotEnabled(_) => 502,
+            ConfIOReason::UnresolvedVars(_) => 503,
+            ConfIOReason::RequiredVar { .. } => 504,
+            ConfIOReason::Parse { .. } => 505,
+            ConfIOReason::BinaryFormat(_) => 506,
+            ConfIOReason::Validation(_) => 507,
         }
     }
 }
@@ -0,0 +1,782 @@
+use orion_error::ToStructError;
+use serde_derive::Serialize;
+use std::{fmt::Display, path::Path};
+
+#[allow(unused_imports)]
+use crate::diagnostic::{ParseFailure, Tracked, deserialize_tracked, parse_error};
+use crate::error::{ConfIOReason, OrionConfResult};
+use crate::value::Tree;
+#[cfg(any(feature = "ini", feature = "dotenv", feature = "properties"))]
+use crate::value::from_value_lenient;
+
+/// 配置文件格式
+///
+/// 格式变体总是存在，是否可用由对应的 cargo feature 决定（见 [`ConfFormat::is_enabled`]）。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum ConfFormat {
+    Yaml,
+    Toml,
+    Json,
+    Json5,
+    Ini,
+    Ron,
+    Dotenv,
+    Hcl,
+    Properties,
+    Msgpack,
+    Cbor,
+}
+
+impl ConfFormat {
+    /// 按默认优先级排列的全部格式：YAML > TOML > JSON > INI > JSON5 > RON > DOTENV > HCL > PROPERTIES > MSGPACK > CBOR
+    pub const ALL: [ConfFormat; 11] = [
+        ConfFormat::Yaml,
+        ConfFormat::Toml,
+        ConfFormat::Json,
+        ConfFormat::Ini,
+        ConfFormat::Json5,
+        ConfFormat::Ron,
+        ConfFormat::Dotenv,
+        ConfFormat::Hcl,
+        ConfFormat::Properties,
+        ConfFormat::Msgpack,
+        ConfFormat::Cbor,
+    ];
+
+    /// 格式名称，同时也是对应的 feature 名
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfFormat::Yaml => "yaml",
+            ConfFormat::Toml => "toml",
+            ConfFormat::Json => "json",
+            ConfFormat::Json5 => "json5",
+            ConfFormat::Ini => "ini",
+            ConfFormat::Ron => "ron",
+            ConfFormat::Dotenv => "dotenv",
+            ConfFormat::Hcl => "hcl",
+            ConfFormat::Properties => "properties",
+            ConfFormat::Msgpack => "msgpack",
+            ConfFormat::Cbor => "cbor",
+        }
+    }
+
+    /// 保存文件时使用的标准扩展名（不含 `.`）
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfFormat::Dotenv => "env",
+            _ => self.name(),
+        }
+    }
+
+    /// 根据扩展名识别格式（大小写不敏感，不含 `.`）
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(ConfFormat::Yaml),
+            "toml" => Some(ConfFormat::Toml),
+            "json" => Some(ConfFormat::Json),
+            "json5" | "jsonc" => Some(ConfFormat::Json5),
+            "ini" => Some(ConfFormat::Ini),
+            "ron" => Some(ConfFormat::Ron),
+            "env" => Some(ConfFormat::Dotenv),
+            "hcl" | "tfvars" => Some(ConfFormat::Hcl),
+            "properties" => Some(ConfFormat::Properties),
+            "msgpack" | "mpk" => Some(ConfFormat::Msgpack),
+            "cbor" => Some(ConfFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// 根据路径扩展名识别格式，`.env`、`.env.local` 这类文件名识别为 dotenv
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let by_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension);
+        by_extension.or_else(|| {
+            let name = path.file_name()?.to_str()?;
+            (name == ".env" || name.starts_with(".env.")).then_some(ConfFormat::Dotenv)
+        })
+    }
+
+    /// 当前编译配置中是否启用了该格式
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            ConfFormat::Yaml => cfg!(feature = "yaml"),
+            ConfFormat::Toml => cfg!(feature = "toml"),
+            ConfFormat::Json => cfg!(feature = "json"),
+            ConfFormat::Json5 => cfg!(feature = "json5"),
+            ConfFormat::Ini => cfg!(feature = "ini"),
+            ConfFormat::Ron => cfg!(feature = "ron"),
+            ConfFormat::Dotenv => cfg!(feature = "dotenv"),
+            ConfFormat::Hcl => cfg!(feature = "hcl"),
+            ConfFormat::Properties => cfg!(feature = "properties"),
+            ConfFormat::Msgpack => cfg!(feature = "msgpack"),
+            ConfFormat::Cbor => cfg!(feature = "cbor"),
+        }
+    }
+
+    /// 二进制格式只能按字节整体读写，不能作为文本解析，也不支持环境变量替换
+    pub fn is_binary(&self) -> bool {
+        matches!(self, ConfFormat::Msgpack | ConfFormat::Cbor)
+    }
+
+    /// 按特性优先级选出的默认格式，未启用任何格式时返回 `None`
+    pub fn default_enabled() -> Option<Self> {
+        Self::ALL.into_iter().find(|fmt| fmt.is_enabled())
+    }
+
+    /// 为路径选择格式：优先按扩展名，未知扩展名时回退到特性优先级
+    ///
+    /// 扩展名对应的格式未启用时返回 [`ConfIOReason::FormatNotEnabled`]。
+    pub fn resolve(path: &Path) -> OrionConfResult<Self> {
+        match Self::from_path(path) {
+            Some(fmt) if fmt.is_enabled() => Ok(fmt),
+            Some(fmt) if Self::default_enabled().is_some() => {
+                Err(ConfIOReason::FormatNotEnabled(fmt).to_err())
+            }
+            _ => Self::default_enabled().ok_or_else(|| ConfIOReason::NoFormatEnabled.to_err()),
+        }
+    }
+
+    /// 将文本解析为格式无关的值树
+    ///
+    /// YAML 带标签的枚举按外部标记保存（`!Slow 3` 读作 `{"Slow": 3}`）。RON 的枚举变体名无法进入
+    /// 值树（`Slow(3)` 只能读作 `[3]`），含枚举变体的 RON 文本在这里报错，需要枚举的 RON 配置应直接按 `T` 加载。
+    /// JSON / JSON5 顶层的 `$schema` 编辑器关联不进入值树，值树再按 `T` 反序列化时不会被当作未知字段。
+    #[cfg_attr(
+        not(any(
+            feature = "yaml",
+            feature = "toml",
+            feature = "json",
+            feature = "json5",
+            feature = "ini",
+            feature = "ron",
+            feature = "dotenv",
+            feature = "hcl",
+            feature = "properties"
+        )),
+        allow(unused_variables)
+    )]
+    pub(crate) fn parse_value(
+        &self,
+        content: &str,
+        path: Option<&Path>,
+    ) -> OrionConfResult<serde_json::Value> {
+        let tree: Tree = match self {
+            #[cfg(feature = "yaml")]
+            ConfFormat::Yaml => {
+                yaml_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "toml")]
+            ConfFormat::Toml => {
+                toml_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "json")]
+            ConfFormat::Json => {
+                json_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "json5")]
+            ConfFormat::Json5 => {
+                json5_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "ini")]
+            ConfFormat::Ini => {
+                ini_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "ron")]
+            ConfFormat::Ron => {
+                let tree =
+                    ron_from_str(content).map_err(|e| parse_error(*self, path, content, e))?;
+                if let Some(err) = ron_named_value(content) {
+                    return Err(parse_error(*self, path, content, err));
+                }
+                Ok(tree)
+            }
+            #[cfg(feature = "dotenv")]
+            ConfFormat::Dotenv => {
+                dotenv_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "hcl")]
+            ConfFormat::Hcl => {
+                hcl_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            #[cfg(feature = "properties")]
+            ConfFormat::Properties => {
+                properties_from_str(content).map_err(|e| parse_error(*self, path, content, e))
+            }
+            fmt if fmt.is_binary() && fmt.is_enabled() => {
+                Err(ConfIOReason::BinaryFormat(*fmt).to_err())
+            }
+            #[allow(unreachable_patterns)]
+            fmt => Err(ConfIOReason::FormatNotEnabled(*fmt).to_err()),
+        }?;
+        let mut value = tree.0;
+        if matches!(self, ConfFormat::Json | ConfFormat::Json5)
+            && let Some(map) = value.as_object_mut()
+        {
+            map.remove("$schema");
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "yaml")]
+pub(crate) fn yaml_from_str<T>(content: &str) -> Result<T, Tracked<serde_yaml::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    deserialize_tracked(serde_yaml::Deserializer::from_str(content))
+}
+
+#[cfg(feature = "toml")]
+pub(crate) fn toml_from_str<T>(content: &str) -> Result<T, Tracked<toml::de::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    deserialize_tracked(toml::Deserializer::parse(content).map_err(Tracked::untracked)?)
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn json_from_str<T>(content: &str) -> Result<T, Tracked<serde_json::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+    let value = deserialize_tracked(SkipSchemaKey(&mut deserializer))?;
+    // 与 serde_json::from_str 一致，拒绝尾随内容
+    deserializer.end().map_err(Tracked::untracked)?;
+    Ok(value)
+}
+
+/// 兼容 JSONC：允许注释、尾随逗号和不加引号的键
+#[cfg(feature = "json5")]
+pub(crate) fn json5_from_str<T>(content: &str) -> Result<T, Tracked<json5::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // 整个文本先按语法解析，无需额外的尾随内容检查
+    let mut deserializer = json5::Deserializer::from_str(content).map_err(Tracked::untracked)?;
+    deserialize_tracked(SkipSchemaKey(&mut deserializer))
+}
+
+/// 顶层结构体忽略保存时写入的 `$schema` 编辑器关联，`deny_unknown_fields` 的类型也能读回
+///
+/// 只处理顶层且目标结构体本身没有 `$schema` 字段的情况，其余调用原样转发；经值树加载的路径由
+/// [`ConfFormat::parse_value`] 去掉该键。
+#[cfg(any(feature = "json", feature = "json5"))]
+struct SkipSchemaKey<D>(D);
+
+#[cfg(any(feature = "json", feature = "json5"))]
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                self.0.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+impl<'de, D> serde::Deserializer<'de> for SkipSchemaKey<D>
+where
+    D: serde::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if fields.contains(&SCHEMA_KEY) {
+            self.0.deserialize_struct(name, fields, visitor)
+        } else {
+            self.0
+                .deserialize_struct(name, fields, SkipSchemaKey(visitor))
+        }
+    }
+
+    forward_deserialize! {
+        deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(),
+        deserialize_i32(), deserialize_i64(), deserialize_i128(), deserialize_u8(),
+        deserialize_u16(), deserialize_u32(), deserialize_u64(), deserialize_u128(),
+        deserialize_f32(), deserialize_f64(), deserialize_char(), deserialize_str(),
+        deserialize_string(), deserialize_bytes(), deserialize_byte_buf(), deserialize_option(),
+        deserialize_unit(), deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str), deserialize_seq(),
+        deserialize_tuple(len: usize), deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(), deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(), deserialize_ignored_any(),
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+const SCHEMA_KEY: &str = "$schema";
+
+#[cfg(any(feature = "json", feature = "json5"))]
+impl<'de, V> serde::de::Visitor<'de> for SkipSchemaKey<V>
+where
+    V: serde::de::Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        self.0.visit_seq(seq)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        self.0.visit_map(SkipSchemaKey(map))
+    }
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+impl<'de, A> serde::de::MapAccess<'de> for SkipSchemaKey<A>
+where
+    A: serde::de::MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        use serde::de::IntoDeserializer;
+
+        while let Some(key) = self.0.next_key::<String>()? {
+            if key != SCHEMA_KEY {
+                return seed.deserialize(key.into_deserializer()).map(Some);
+            }
+            self.0.next_value::<serde::de::IgnoredAny>()?;
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, A::Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+/// `[a.b]` 节映射嵌套结构，重复的键和 `key[]` 映射数组，值按目标类型宽松转换
+#[cfg(feature = "ini")]
+pub(crate) fn ini_from_str<T>(content: &str) -> Result<T, Box<dyn ParseFailure>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let tree = crate::ini::parse(content).map_err(|e| Box::new(e) as Box<dyn ParseFailure>)?;
+    from_value_lenient(tree).map_err(|e| Box::new(e) as Box<dyn ParseFailure>)
+}
+
+/// 反序列化错误转换为带位置的 `SpannedError`
+#[cfg(feature = "ron")]
+pub(crate) fn ron_from_str<T>(content: &str) -> Result<T, Tracked<Box<ron::error::SpannedError>>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let mut deserializer =
+        ron::Deserializer::from_str(content).map_err(|e| Tracked::untracked(Box::new(e)))?;
+    let value = deserialize_tracked(&mut deserializer)
+        .map_err(|e| e.map(|code| Box::new(deserializer.span_error(code))))?;
+    // 与 ron::from_str 一致，拒绝尾随内容
+    deserializer
+        .end()
+        .map_err(|code| Tracked::untracked(Box::new(deserializer.span_error(code))))?;
+    Ok(value)
+}
+
+/// 查找根节点以外的枚举变体或具名结构（`Slow(3)`、`Fast`、`Point(x: 1)`）
+///
+/// RON 在 `deserialize_any` 中丢弃这些名字，值树无法保留；根节点的结构名不影响加载。
+#[cfg(feature = "ron")]
+fn ron_named_value(content: &str) -> Option<crate::diagnostic::SyntaxError> {
+    const KEYWORDS: [&str; 6] = ["true", "false", "Some", "None", "inf", "NaN"];
+
+    let chars: Vec<char> = content.chars().collect();
+    let mut cursor = RonCursor {
+        chars: &chars,
+        index: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut seen_value = false;
+    loop {
+        cursor.skip_blank();
+        let c = cursor.peek(0)?;
+        match c {
+            // `#![enable(..)]` 扩展属性
+            '#' if !seen_value => {
+                cursor.skip_until(|c| c == ']');
+                cursor.bump();
+                continue;
+            }
+            '"' | '\'' => {
+                cursor.bump();
+                while let Some(next) = cursor.bump() {
+                    match next {
+                        '\\' => {
+                            cursor.bump();
+                        }
+                        next if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            // 字节串 `b"..."` 与字节 `b'x'`：跳过前缀，引号部分按普通字符串处理
+            'b' if matches!(cursor.peek(1), Some('"' | '\'')) => {
+                cursor.bump();
+                continue;
+            }
+            // 原始字符串 `r"..."` / `r#"..."#` / `br"..."`
+            'r' | 'b' if cursor.raw_string_prefix().is_some() => {
+                let prefix = cursor.raw_string_prefix().unwrap_or_default();
+                (0..prefix).for_each(|_| {
+                    cursor.bump();
+                });
+                let mut hashes = 0;
+                while cursor.peek(0) == Some('#') {
+                    cursor.bump();
+                    hashes += 1;
+                }
+                cursor.bump();
+                while let Some(next) = cursor.bump() {
+                    if next == '"' && (0..hashes).all(|i| cursor.peek(i) == Some('#')) {
+                        (0..hashes).for_each(|_| {
+                            cursor.bump();
+                        });
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                cursor.skip_until(|c| !(c.is_alphanumeric() || c == '_' || c == '.'));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let (line, column, start) = (cursor.line, cursor.column, cursor.index);
+                cursor.skip_until(|c| !(c.is_alphanumeric() || c == '_'));
+                let name: String = chars[start..cursor.index].iter().collect();
+                let mut lookahead = cursor.clone();
+                lookahead.skip_blank();
+                let is_field = lookahead.peek(0) == Some(':');
+                if seen_value && !is_field && !KEYWORDS.contains(&name.as_str()) {
+                    return Some(crate::diagnostic::SyntaxError {
+                        line,
+                        column,
+                        message: format!(
+                            "enum variant or struct name `{name}` is not kept by structured \
+                             substitution, overlays or layers; load RON enums directly"
+                        ),
+                    });
+                }
+            }
+            _ => {
+                cursor.bump();
+            }
+        }
+        seen_value = true;
+    }
+}
+
+#[cfg(feature = "ron")]
+#[derive(Clone)]
+struct RonCursor<'a> {
+    chars: &'a [char],
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+#[cfg(feature = "ron")]
+impl RonCursor<'_> {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.index += 1;
+        if c == '\n' {
+            (self.line, self.column) = (self.line + 1, 1);
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// 原始字符串前缀 `r` / `br` 的长度，后面须跟 `"` 或 `#`
+    fn raw_string_prefix(&self) -> Option<usize> {
+        let prefix = match (self.peek(0), self.peek(1)) {
+            (Some('r'), _) => 1,
+            (Some('b'), Some('r')) => 2,
+            _ => return None,
+        };
+        let mut offset = prefix;
+        while self.peek(offset) == Some('#') {
+            offset += 1;
+        }
+        (self.peek(offset) == Some('"')).then_some(prefix)
+    }
+
+    fn skip_until(&mut self, stop: impl Fn(char) -> bool) {
+        while self.peek(0).is_some_and(|c| !stop(c)) {
+            self.bump();
+        }
+    }
+
+    /// 跳过空白和注释（块注释可嵌套）
+    fn skip_blank(&mut self) {
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => self.skip_until(|c| c == '\n'),
+                (Some('/'), Some('*')) => {
+                    let mut depth = 0;
+                    while let Some(c) = self.bump() {
+                        match (c, self.peek(0)) {
+                            ('/', Some('*')) => depth += 1,
+                            ('*', Some('/')) => depth -= 1,
+                            _ => continue,
+                        }
+                        self.bump();
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+}
+
+/// 键名转为小写以对应 snake_case 字段，值按目标类型宽松转换
+///
+/// `${VAR}` 只引用文件中在前面出现的键，其余占位符原样保留。
+#[cfg(feature = "dotenv")]
+pub(crate) fn dotenv_from_str<T>(content: &str) -> Result<T, Box<dyn ParseFailure>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    use crate::diagnostic::SyntaxError;
+    use crate::dotenv::{parse, resolve, to_tree};
+
+    let entries = parse(content).map_err(|e| Box::new(e) as Box<dyn ParseFailure>)?;
+    let pairs = resolve(
+        entries,
+        &orion_variate::EnvDict::new(),
+        false,
+        &mut Vec::new(),
+    )
+    .map_err(|(line, e)| {
+        Box::new(SyntaxError {
+            line,
+            column: 1,
+            message: e.reason().to_string(),
+        }) as Box<dyn ParseFailure>
+    })?;
+    from_value_lenient(to_tree(pairs)).map_err(|e| Box::new(e) as Box<dyn ParseFailure>)
+}
+
+/// 只支持属性与对象；未替换的 `${...}` 按 HCL 模板原样保留在字符串中
+#[cfg(feature = "hcl")]
+pub(crate) fn hcl_from_str<T>(content: &str) -> Result<T, Tracked<hcl::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    deserialize_tracked(hcl::de::Deserializer::from_str(content).map_err(Tracked::untracked)?)
+}
+
+/// 点分键展开为嵌套结构，下标键（`list.0`）展开为数组，值按目标类型宽松转换
+#[cfg(feature = "properties")]
+pub(crate) fn properties_from_str<T>(content: &str) -> Result<T, Box<dyn ParseFailure>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let tree = crate::properties::parse(content)
+        .and_then(crate::properties::nest)
+        .map_err(|e| Box::new(e) as Box<dyn ParseFailure>)?;
+    from_value_lenient(tree).map_err(|e| Box::new(e) as Box<dyn ParseFailure>)
+}
+
+/// 结构体可以是 map（带字段名）或数组形式
+#[cfg(feature = "msgpack")]
+pub(crate) fn msgpack_from_slice<T>(data: &[u8]) -> Result<T, Tracked<rmp_serde::decode::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    deserialize_tracked(&mut rmp_serde::Deserializer::from_read_ref(data))
+}
+
+/// ciborium 不公开其反序列化器，错误不带键路径
+#[cfg(feature = "cbor")]
+pub(crate) fn cbor_from_slice<T>(data: &[u8]) -> Result<T, ciborium::de::Error<std::io::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    ciborium::de::from_reader(data)
+}
+
+impl Display for ConfFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path_detects_known_extensions() {
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.yml")),
+            Some(ConfFormat::Yaml)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("conf/app.YAML")),
+            Some(ConfFormat::Yaml)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.toml")),
+            Some(ConfFormat::Toml)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.json")),
+            Some(ConfFormat::Json)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.json5")),
+            Some(ConfFormat::Json5)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new(".vscode/settings.jsonc")),
+            Some(ConfFormat::Json5)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.ini")),
+            Some(ConfFormat::Ini)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.ron")),
+            Some(ConfFormat::Ron)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new(".env")),
+            Some(ConfFormat::Dotenv)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("deploy/.env.local")),
+            Some(ConfFormat::Dotenv)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("prod.env")),
+            Some(ConfFormat::Dotenv)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new(".env.json")),
+            Some(ConfFormat::Json)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.hcl")),
+            Some(ConfFormat::Hcl)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("prod.tfvars")),
+            Some(ConfFormat::Hcl)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("application.properties")),
+            Some(ConfFormat::Properties)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("cache/app.mpk")),
+            Some(ConfFormat::Msgpack)
+        );
+        assert_eq!(
+            ConfFormat::from_path(Path::new("app.cbor")),
+            Some(ConfFormat::Cbor)
+        );
+        assert_eq!(ConfFormat::from_path(Path::new("app.conf")), None);
+        assert_eq!(ConfFormat::from_path(Path::new("app")), None);
+    }
+
+    #[test]
+    fn test_resolve_unknown_extension_falls_back_to_priority() {
+        let resolved = ConfFormat::resolve(Path::new("app.conf"));
+        match ConfFormat::default_enabled() {
+            Some(fmt) => assert_eq!(resolved.ok(), Some(fmt)),
+            None => assert_eq!(
+                resolved.err().map(|e| e.reason().clone()),
+                Some(ConfIOReason::NoFormatEnabled)
+            ),
+        }
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_named_value() {
+        let found = |content: &str| ron_named_value(content).map(|e| (e.line, e.column));
+        assert_eq!(found("Config(mode: Fast)"), Some((1, 14)));
+        assert_eq!(found("(a: [1, 2],\n b: Slow(3))"), Some((2, 5)));
+        assert_eq!(
+            found("#![enable(unwrap_newtypes)]\nConfig(a: Some(1), b: None, c: true, d: inf)"),
+            None
+        );
+        assert_eq!(
+            found(r##"(a: "Fast", b: 'x', c: r#"Slow(1)"#, d: b"Fast", e: b'F', f: 1e5)"##),
+            None
+        );
+        assert_eq!(
+            found("(/* Fast /* nested */ */ a: 1, // Slow\n brand: 2)"),
+            None
+        );
+        assert_eq!(found("(a: Some(Fast))"), Some((1, 10)));
+    }
+
+    #[test]
+    fn test_resolve_disabled_extension() {
+        for fmt in ConfFormat::ALL {
+            let path = format!("app.{}", fmt.extension());
+            let resolved = ConfFormat::resolve(Path::new(&path));
+            if fmt.is_enabled() {
+                assert_eq!(resolved.ok(), Some(fmt));
+            } else if ConfFormat::default_enabled().is_some() {
+                assert_eq!(
+                    resolved.err().map(|e| e.reason().clone()),
+                    Some(ConfIOReason::FormatNotEnabled(fmt))
+                );
+            } else {
+                assert!(resolved.is_err());
+            }
+        }
+    }
+}
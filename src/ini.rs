@@ -0,0 +1,243 @@
+//! INI 文本的解析与转义
+//!
+//! 解析结果是字符串叶子的值树，数字和布尔值由宽松反序列化按目标类型转换。
+
+use serde_json::{Map, Value};
+
+use crate::diagnostic::SyntaxError;
+
+/// 解析为值树
+///
+/// - `;` / `#` 开头的行是注释；值后面空白加 `;` / `#` 开始行尾注释（引号内除外）
+/// - `[a.b]` 表示嵌套节，节名按 `.` 分段
+/// - 重复的键和 `key[]` 收集为数组
+/// - 值可以用引号包裹：单引号原样保留，双引号内支持 `\\`、`\"`、`\n`、`\r`、`\t` 转义
+pub(crate) fn parse(content: &str) -> Result<Value, SyntaxError> {
+    let mut root = Map::new();
+    let mut section: Vec<String> = Vec::new();
+    for (index, raw) in content.lines().enumerate() {
+        let line = index + 1;
+        let start = raw.len() - raw.trim_start().len();
+        let text = raw.trim();
+        let error = |offset: usize, message: &str| SyntaxError {
+            line,
+            column: raw[..start + offset].chars().count() + 1,
+            message: message.to_string(),
+        };
+        if text.is_empty() || text.starts_with([';', '#']) {
+            continue;
+        }
+        if let Some(rest) = text.strip_prefix('[') {
+            let Some((name, tail)) = rest.split_once(']') else {
+                return Err(error(0, "unterminated section header"));
+            };
+            if !is_comment_or_blank(tail) {
+                return Err(error(
+                    name.len() + 2,
+                    "unexpected content after section header",
+                ));
+            }
+            let segments: Vec<String> = name.split('.').map(|s| s.trim().to_string()).collect();
+            if segments.iter().any(String::is_empty) {
+                return Err(error(1, "invalid section name"));
+            }
+            table(&mut root, &segments).ok_or_else(|| error(1, "section conflicts with a key"))?;
+            section = segments;
+            continue;
+        }
+        let Some(separator) = text.find(['=', ':']) else {
+            return Err(error(0, "expected `key = value`"));
+        };
+        let key = text[..separator].trim_end();
+        let (key, is_array) = match key.strip_suffix("[]") {
+            Some(key) => (key.trim_end(), true),
+            None => (key, false),
+        };
+        if key.is_empty() {
+            return Err(error(0, "missing key"));
+        }
+        let rest = &text[separator + 1..];
+        let value_offset = separator + 1 + (rest.len() - rest.trim_start().len());
+        let value = parse_value(rest.trim_start())
+            .map_err(|(offset, message)| error(value_offset + offset, message))?;
+
+        let conflict = || error(0, "key conflicts with a section");
+        let node = table(&mut root, &section).ok_or_else(conflict)?;
+        match node.get_mut(key) {
+            None if is_array => {
+                node.insert(key.to_string(), Value::Array(vec![value]));
+            }
+            None => {
+                node.insert(key.to_string(), value);
+            }
+            Some(Value::Array(list)) => list.push(value),
+            Some(Value::Object(_)) => return Err(conflict()),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, value]);
+            }
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+/// 沿路径取得（必要时创建）节；路径上已有非节的值时返回 `None`
+fn table<'a>(
+    root: &'a mut Map<String, Value>,
+    path: &[String],
+) -> Option<&'a mut Map<String, Value>> {
+    let mut node = root;
+    for segment in path {
+        node = node
+            .entry(segment.as_str())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()?;
+    }
+    Some(node)
+}
+
+fn is_comment_or_blank(text: &str) -> bool {
+    let text = text.trim_start();
+    text.is_empty() || text.starts_with([';', '#'])
+}
+
+/// 解析去掉前导空白的值；错误带有相对值起点的字节偏移
+fn parse_value(text: &str) -> Result<Value, (usize, &'static str)> {
+    let Some(quote) = text.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+        // 行尾注释需要前置空白，`color=#fff` 中的 `#` 属于值
+        let end = text
+            .char_indices()
+            .find(|&(i, c)| matches!(c, ';' | '#') && text[..i].ends_with(char::is_whitespace))
+            .map_or(text.len(), |(i, _)| i);
+        return Ok(Value::String(text[..end].trim_end().to_string()));
+    };
+    let mut out = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => {
+                if !is_comment_or_blank(&text[i + 1..]) {
+                    return Err((i + 1, "unexpected content after quoted value"));
+                }
+                return Ok(Value::String(out));
+            }
+            '\\' if quote == '"' => match chars.next() {
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, 'r')) => out.push('\r'),
+                Some((_, 't')) => out.push('\t'),
+                Some((_, c @ ('\\' | '"'))) => out.push(c),
+                _ => return Err((i, "invalid escape in quoted value")),
+            },
+            c => out.push(c),
+        }
+    }
+    Err((0, "unterminated quoted value"))
+}
+
+/// 键可以原样写出：非空、无首尾空白，且不含分隔符、节括号和换行
+pub(crate) fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.trim() == key
+        && !key.starts_with([';', '#'])
+        && !key.contains(['=', ':', '[', ']', '\n', '\r'])
+}
+
+/// 值在读回时会被改变（首尾空白、引号开头、换行、行尾注释）时加双引号并转义
+pub(crate) fn quote_value(value: &str) -> String {
+    let needs_quote = value.trim() != value
+        || value.starts_with(['"', '\''])
+        || value.contains(['\n', '\r'])
+        || value
+            .char_indices()
+            .any(|(i, c)| matches!(c, ';' | '#') && value[..i].ends_with(char::is_whitespace));
+    if !needs_quote {
+        return value.to_string();
+    }
+    let mut out = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_sections_arrays_and_comments() {
+        let content = r#"
+; leading comment
+# another comment
+name = svc ; inline comment
+color=#fff
+tags[] = a
+tags[] = b
+hosts = h1
+hosts = h2
+
+[db]
+url: "pg://local ; not a comment"
+[db.pool]
+size = 4   # inline
+label = '  spaced  '
+"#;
+        assert_eq!(
+            parse(content).unwrap(),
+            json!({
+                "name": "svc",
+                "color": "#fff",
+                "tags": ["a", "b"],
+                "hosts": ["h1", "h2"],
+                "db": {
+                    "url": "pg://local ; not a comment",
+                    "pool": {"size": "4", "label": "  spaced  "}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_have_location() {
+        let err = parse("[ok]\n  just text\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.message, "expected `key = value`");
+
+        let err = parse("a = \"open\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+
+        let err = parse("a = 1\n[a.b]\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.message.as_str()),
+            (2, "section conflicts with a key")
+        );
+
+        let err = parse("[db\n").unwrap_err();
+        assert_eq!(err.message, "unterminated section header");
+    }
+
+    #[test]
+    fn test_quote_value_round_trip() {
+        for value in [
+            "plain",
+            "#fff",
+            " lead",
+            "a ; b",
+            "line\nbreak",
+            "\"q\" \\ x",
+            "",
+        ] {
+            let line = format!("k={}", quote_value(value));
+            assert_eq!(parse(&line).unwrap(), json!({ "k": value }), "{line}");
+        }
+    }
+}
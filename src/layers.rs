@@ -0,0 +1,550 @@
+use orion_error::{ContextRecord, ErrorOwe, ErrorWith, OperationContext, ToStructError};
+use orion_variate::EnvDict;
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::diagnostic::{ParseFailure, parse_error};
+use crate::env::{EnvOverlay, UnresolvedPolicy, substitute};
+use crate::error::{ConfIOReason, OrionConfResult};
+use crate::format::ConfFormat;
+use crate::persist::record_key_path;
+#[cfg(feature = "schema-validate")]
+use crate::validate::SchemaValidator;
+use crate::value::from_value_lenient;
+
+/// 合并时数组的处理方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// 后加载的数组整体替换之前的数组
+    #[default]
+    Replace,
+    /// 后加载的数组追加到之前的数组末尾
+    Append,
+}
+
+#[derive(Clone, Debug)]
+enum LayerSource {
+    File { path: PathBuf, required: bool },
+    Content { format: ConfFormat, content: String },
+}
+
+/// 已加载的层
+struct LoadedLayer {
+    format: ConfFormat,
+    path: Option<PathBuf>,
+    value: Value,
+}
+
+/// 分层配置构建器
+///
+/// 按添加顺序加载各层到格式无关的值树，逐层深度合并映射，最后一次性反序列化为 `T`。
+/// 每层可以使用不同的格式，例如 `base.yaml` + `prod.toml` + `local.json`。
+#[derive(Clone, Debug, Default)]
+pub struct ConfigLayers {
+    layers: Vec<LayerSource>,
+    array_merge: ArrayMerge,
+    overlay: Option<EnvOverlay>,
+    env_dict: Option<EnvDict>,
+    unresolved: UnresolvedPolicy,
+    #[cfg(feature = "schema-validate")]
+    schema: Option<SchemaValidator>,
+}
+
+impl ConfigLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加文件层，格式按扩展名选择，文件不存在时报错
+    pub fn with_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.layers.push(LayerSource::File {
+            path: path.into(),
+            required: true,
+        });
+        self
+    }
+
+    /// 添加可选文件层，文件不存在时跳过
+    pub fn with_optional_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.layers.push(LayerSource::File {
+            path: path.into(),
+            required: false,
+        });
+        self
+    }
+
+    /// 添加内存中的文本层
+    pub fn with_content<S: Into<String>>(mut self, format: ConfFormat, content: S) -> Self {
+        self.layers.push(LayerSource::Content {
+            format,
+            content: content.into(),
+        });
+        self
+    }
+
+    pub fn with_array_merge(mut self, array_merge: ArrayMerge) -> Self {
+        self.array_merge = array_merge;
+        self
+    }
+
+    /// 对每一层的文本执行 `${VAR}` 替换
+    pub fn with_env_dict(mut self, dict: EnvDict) -> Self {
+        self.env_dict = Some(dict);
+        self
+    }
+
+    /// 该加载器中未解析占位符的处理策略，默认为 [`UnresolvedPolicy::Warn`]
+    pub fn with_unresolved(mut self, policy: UnresolvedPolicy) -> Self {
+        self.unresolved = policy;
+        self
+    }
+
+    /// 所有层合并后应用环境变量覆盖
+    pub fn with_env_overlay(mut self, overlay: EnvOverlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    /// 合并后、反序列化前按 JSON Schema 校验，违规项只带键路径（合并结果没有单一的源文件）
+    #[cfg(feature = "schema-validate")]
+    pub fn with_schema(mut self, schema: SchemaValidator) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// 加载并合并所有层，返回合并后的值树
+    pub fn merged_value(&self) -> OrionConfResult<Value> {
+        let layers = self.load_layers()?;
+        Ok(self.merge(layers.into_iter().map(|layer| layer.value)))
+    }
+
+    /// 加载并合并所有层，再反序列化为 `T`（字符串值可按目标类型转换为数字或布尔值）
+    pub fn load<T>(&self) -> OrionConfResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut ctx = OperationContext::want("load object from config layers").with_auto_log();
+        ctx.record("layers", self.layers.len().to_string());
+        let layers = self.load_layers().with(&ctx)?;
+        #[cfg_attr(not(feature = "schema-validate"), allow(unused_mut))]
+        let mut merged = self.merge(layers.iter().map(|layer| layer.value.clone()));
+        #[cfg(feature = "schema-validate")]
+        if let Some(schema) = &self.schema {
+            schema
+                .validate_value(&mut merged)
+                .map_err(|violations| {
+                    let violations = violations.into_iter().map(|(v, _)| v).collect();
+                    ConfIOReason::Validation(violations).to_err()
+                })
+                .with(&ctx)?;
+        }
+        let loaded = from_value_lenient(merged).map_err(|e| {
+            // 错误归到定义了出错键的最后一层，找不到时归到最后一层
+            let source = e
+                .key_path()
+                .and_then(|key_path| {
+                    layers
+                        .iter()
+                        .rev()
+                        .find(|layer| contains_key_path(&layer.value, &key_path))
+                })
+                .or(layers.last());
+            match source {
+                Some(layer) => parse_error(layer.format, layer.path.as_deref(), "", e),
+                None => ConfIOReason::from(e.to_string()).to_err(),
+            }
+        });
+        let loaded = record_key_path(&mut ctx, loaded).with(&ctx)?;
+        ctx.mark_suc();
+        Ok(loaded)
+    }
+
+    fn load_layers(&self) -> OrionConfResult<Vec<LoadedLayer>> {
+        let mut loaded = Vec::new();
+        for (index, layer) in self.layers.iter().enumerate() {
+            if let Some(layer) = self.load_layer(index, layer)? {
+                loaded.push(layer);
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn merge(&self, values: impl Iterator<Item = Value>) -> Value {
+        let mut merged = Value::Object(Default::default());
+        for value in values {
+            merge_value(&mut merged, value, self.array_merge);
+        }
+        if let Some(overlay) = &self.overlay {
+            overlay.apply(&mut merged);
+        }
+        merged
+    }
+
+    fn load_layer(
+        &self,
+        index: usize,
+        layer: &LayerSource,
+    ) -> OrionConfResult<Option<LoadedLayer>> {
+        let mut ctx = OperationContext::want("load config layer").with_auto_log();
+        ctx.record("layer", index.to_string());
+        let (format, path, content) = match layer {
+            LayerSource::File { path, required } => {
+                ctx.record("from path", path);
+                if !required && !path.exists() {
+                    ctx.mark_suc();
+                    return Ok(None);
+                }
+                let format = ConfFormat::resolve(path).with(&ctx)?;
+                if format.is_binary() {
+                    return Err(ConfIOReason::BinaryFormat(format).to_err()).with(&ctx);
+                }
+                let content = fs::read_to_string(path).owe_res().with(&ctx)?;
+                (format, Some(path.as_path()), content)
+            }
+            LayerSource::Content { format, content } => {
+                ctx.record("source", format!("inline {format} content"));
+                (*format, None, content.clone())
+            }
+        };
+        let content = match &self.env_dict {
+            Some(dict) => substitute(&content, dict, self.unresolved).with(&ctx)?,
+            None => content,
+        };
+        let value = format.parse_value(&content, path).with(&ctx)?;
+        ctx.mark_suc();
+        Ok(Some(LoadedLayer {
+            format,
+            path: path.map(Path::to_path_buf),
+            value,
+        }))
+    }
+}
+
+/// 值树中是否存在键路径，如 `database.hosts[0]`
+fn contains_key_path(value: &Value, key_path: &str) -> bool {
+    let mut node = value;
+    for segment in key_path.split('.') {
+        let (key, indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {
+            match node.get(key) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+        for index in indices.split(['[', ']']).filter(|index| !index.is_empty()) {
+            match index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| node.get(index))
+            {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// 将 `over` 深度合并到 `base`：映射逐键合并，其余值整体覆盖
+pub(crate) fn merge_value(base: &mut Value, over: Value, array_merge: ArrayMerge) {
+    match (base, over) {
+        (Value::Object(base_map), Value::Object(over_map)) => {
+            for (key, over_item) in over_map {
+                match base_map.get_mut(&key) {
+                    Some(base_item) => merge_value(base_item, over_item, array_merge),
+                    None => {
+                        base_map.insert(key, over_item);
+                    }
+                }
+            }
+        }
+        (Value::Array(base_list), Value::Array(over_list)) if array_merge == ArrayMerge::Append => {
+            base_list.extend(over_list);
+        }
+        (base, over) => *base = over,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_value_deep_maps() {
+        let mut base = json!({"name": "base", "db": {"host": "localhost", "port": 5432}});
+        merge_value(
+            &mut base,
+            json!({"db": {"host": "db.prod"}, "debug": false}),
+            ArrayMerge::Replace,
+        );
+        assert_eq!(
+            base,
+            json!({"name": "base", "db": {"host": "db.prod", "port": 5432}, "debug": false})
+        );
+    }
+
+    #[test]
+    fn test_merge_value_arrays() {
+        let mut replaced = json!({"hosts": ["a", "b"]});
+        merge_value(&mut replaced, json!({"hosts": ["c"]}), ArrayMerge::Replace);
+        assert_eq!(replaced, json!({"hosts": ["c"]}));
+
+        let mut appended = json!({"hosts": ["a", "b"]});
+        merge_value(&mut appended, json!({"hosts": ["c"]}), ArrayMerge::Append);
+        assert_eq!(appended, json!({"hosts": ["a", "b", "c"]}));
+    }
+
+    #[test]
+    fn test_contains_key_path() {
+        let value = json!({"db": {"hosts": ["a", {"port": 1}]}});
+        assert!(contains_key_path(&value, "db.hosts[1].port"));
+        assert!(!contains_key_path(&value, "db.hosts[2]"));
+        assert!(!contains_key_path(&value, "db.port"));
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml", feature = "json"))]
+    mod formats {
+        use super::*;
+        use serde_derive::Deserialize;
+        use tempfile::NamedTempFile;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct LayeredConfig {
+            name: String,
+            hosts: Vec<String>,
+            database: DatabaseConfig,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct DatabaseConfig {
+            url: String,
+            pool_size: u32,
+        }
+
+        fn create_layer_file(content: &str, extension: &str) -> NamedTempFile {
+            let file = NamedTempFile::with_suffix(extension).unwrap();
+            std::fs::write(file.path(), content).unwrap();
+            file
+        }
+
+        fn create_layer_files() -> (NamedTempFile, NamedTempFile, NamedTempFile) {
+            let base = create_layer_file(
+                r#"
+name: base_app
+hosts: [a, b]
+database:
+  url: postgres://localhost/app
+  pool_size: 4
+"#,
+                ".yaml",
+            );
+            let prod = create_layer_file(
+                r#"
+hosts = ["c"]
+
+[database]
+url = "postgres://db.prod/app"
+"#,
+                ".toml",
+            );
+            let local = create_layer_file(r#"{"database": {"pool_size": 16}}"#, ".json");
+            (base, prod, local)
+        }
+
+        #[test]
+        fn test_layers_merge_across_formats() {
+            let (base, prod, local) = create_layer_files();
+            let loaded: LayeredConfig = ConfigLayers::new()
+                .with_file(base.path())
+                .with_file(prod.path())
+                .with_file(local.path())
+                .load()
+                .expect("Failed to load layered config");
+
+            assert_eq!(
+                loaded,
+                LayeredConfig {
+                    name: "base_app".to_string(),
+                    hosts: vec!["c".to_string()],
+                    database: DatabaseConfig {
+                        url: "postgres://db.prod/app".to_string(),
+                        pool_size: 16,
+                    },
+                }
+            );
+        }
+
+        #[test]
+        fn test_layers_append_arrays() {
+            let (base, prod, _local) = create_layer_files();
+            let loaded: LayeredConfig = ConfigLayers::new()
+                .with_array_merge(ArrayMerge::Append)
+                .with_file(base.path())
+                .with_file(prod.path())
+                .load()
+                .expect("Failed to load layered config");
+            assert_eq!(loaded.hosts, vec!["a", "b", "c"]);
+        }
+
+        #[test]
+        fn test_layers_optional_and_inline() {
+            let (base, _prod, _local) = create_layer_files();
+            let loaded: LayeredConfig = ConfigLayers::new()
+                .with_file(base.path())
+                .with_optional_file("not_exists/local.json")
+                .with_content(ConfFormat::Toml, "name = \"inline_app\"")
+                .load()
+                .expect("Failed to load layered config");
+            assert_eq!(loaded.name, "inline_app");
+            assert_eq!(loaded.database.pool_size, 4);
+        }
+
+        #[test]
+        fn test_layers_error_records_failed_layer() {
+            let (base, _prod, _local) = create_layer_files();
+            let broken = create_layer_file("{ not json", ".json");
+            let result: OrionConfResult<LayeredConfig> = ConfigLayers::new()
+                .with_file(base.path())
+                .with_file(broken.path())
+                .load();
+
+            let err = result.expect_err("broken layer should fail");
+            let rendered = err.to_string();
+            assert!(rendered.contains(&broken.path().display().to_string()));
+            assert!(rendered.contains("layer: 1"));
+        }
+
+        #[test]
+        fn test_layers_env_overlay() {
+            let (base, prod, _local) = create_layer_files();
+            let loaded: LayeredConfig = ConfigLayers::new()
+                .with_file(base.path())
+                .with_file(prod.path())
+                .with_env_overlay(
+                    EnvOverlay::new("APP")
+                        .with_vars([("APP__DATABASE__POOL_SIZE", "32"), ("APP__NAME", "env_app")]),
+                )
+                .load()
+                .expect("Failed to load layered config");
+            assert_eq!(loaded.name, "env_app");
+            assert_eq!(loaded.database.pool_size, 32);
+            assert_eq!(loaded.database.url, "postgres://db.prod/app");
+        }
+
+        #[test]
+        fn test_layers_env_substitution_strict() {
+            use orion_variate::ValueType;
+
+            let base = create_layer_file("name: ${APP_NAME}\nhosts: [a]\n", ".yaml");
+            let db = create_layer_file(
+                "[database]\nurl = \"${ORION_CONF_LAYER_MISSING_URL}\"\npool_size = 2\n",
+                ".toml",
+            );
+            let mut dict = EnvDict::new();
+            dict.insert("APP_NAME", ValueType::from("dict_app"));
+
+            let result: OrionConfResult<LayeredConfig> = ConfigLayers::new()
+                .with_env_dict(dict.clone())
+                .with_unresolved(UnresolvedPolicy::Error)
+                .with_file(base.path())
+                .with_file(db.path())
+                .load();
+            let err = result.expect_err("unresolved var should fail");
+            assert_eq!(
+                err.reason(),
+                &ConfIOReason::UnresolvedVars(vec!["ORION_CONF_LAYER_MISSING_URL".to_string()])
+            );
+            assert!(err.to_string().contains(&db.path().display().to_string()));
+
+            dict.insert("ORION_CONF_LAYER_MISSING_URL", ValueType::from("pg://x"));
+            let loaded: LayeredConfig = ConfigLayers::new()
+                .with_env_dict(dict)
+                .with_unresolved(UnresolvedPolicy::Error)
+                .with_file(base.path())
+                .with_file(db.path())
+                .load()
+                .expect("Failed to load layered config");
+            assert_eq!(loaded.name, "dict_app");
+            assert_eq!(loaded.database.url, "pg://x");
+        }
+
+        #[test]
+        fn test_layers_type_error_reports_key_path() {
+            let (base, prod, _local) = create_layer_files();
+            let local = create_layer_file(r#"{"database": {"pool_size": "many"}}"#, ".json");
+            let result: OrionConfResult<LayeredConfig> = ConfigLayers::new()
+                .with_file(base.path())
+                .with_file(prod.path())
+                .with_file(local.path())
+                .load();
+            let err = result.expect_err("invalid pool size should fail");
+            match err.reason() {
+                ConfIOReason::Parse {
+                    format,
+                    path,
+                    key_path,
+                    ..
+                } => {
+                    assert_eq!(*format, ConfFormat::Json);
+                    assert_eq!(path.as_deref(), Some(local.path()));
+                    assert_eq!(key_path.as_deref(), Some("database.pool_size"));
+                }
+                other => panic!("unexpected reason: {other:?}"),
+            }
+            assert!(err.to_string().contains("key path: database.pool_size"));
+
+            let result: OrionConfResult<LayeredConfig> = ConfigLayers::new()
+                .with_file(base.path())
+                .with_content(ConfFormat::Yaml, "hosts: [a, {x: 1}]")
+                .load();
+            match result.expect_err("invalid host should fail").reason() {
+                ConfIOReason::Parse {
+                    format, key_path, ..
+                } => {
+                    assert_eq!(*format, ConfFormat::Yaml);
+                    assert_eq!(key_path.as_deref(), Some("hosts[1]"));
+                }
+                other => panic!("unexpected reason: {other:?}"),
+            }
+        }
+
+        #[test]
+        fn test_layers_keep_yaml_enums() {
+            #[derive(Debug, Deserialize, PartialEq)]
+            enum Mode {
+                Fast,
+                Slow(u32),
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Worker {
+                mode: Mode,
+                backup: Mode,
+            }
+
+            let loaded: Worker = ConfigLayers::new()
+                .with_content(ConfFormat::Yaml, "mode: Fast\nbackup: !Slow 1\n")
+                .with_content(ConfFormat::Yaml, "mode: !Slow 3\n")
+                .load()
+                .expect("Failed to load layered config");
+            assert_eq!(
+                loaded,
+                Worker {
+                    mode: Mode::Slow(3),
+                    backup: Mode::Slow(1),
+                }
+            );
+        }
+
+        #[test]
+        fn test_layers_missing_required_file() {
+            let result: OrionConfResult<LayeredConfig> =
+                ConfigLayers::new().with_file("not_exists/base.yaml").load();
+            assert!(result.is_err());
+        }
+    }
+}
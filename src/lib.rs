@@ -1,5 +1,34 @@
+mod diagnostic;
+#[cfg(feature = "dotenv")]
+pub mod dotenv;
+pub mod edit;
+pub mod env;
 pub mod error;
+pub mod format;
+#[cfg(feature = "ini")]
+mod ini;
+pub mod layers;
 pub mod persist;
+#[cfg(feature = "properties")]
+mod properties;
+pub mod save;
 pub mod traits;
+pub mod validate;
+mod value;
 
+#[cfg(feature = "dotenv")]
+pub use dotenv::DotenvSource;
+#[cfg(feature = "toml")]
+pub use edit::TomlEditor;
+#[cfg(feature = "yaml")]
+pub use edit::YamlEditor;
+pub use env::{EnvOptions, EnvOverlay, SubstitutionMode, UnresolvedPolicy};
+pub use format::ConfFormat;
+pub use layers::{ArrayMerge, ConfigLayers};
+pub use save::{SaveOptions, TomlTables};
+#[cfg(feature = "schema")]
+pub use schemars::{self, JsonSchema};
 pub use traits::*;
+#[cfg(feature = "schema-validate")]
+pub use validate::SchemaValidator;
+pub use validate::Violation;
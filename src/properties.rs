@@ -0,0 +1,296 @@
+//! Java `.properties` 文本的解析、嵌套与转义
+
+use serde_json::{Map, Value};
+
+use crate::diagnostic::SyntaxError;
+
+/// 一条属性及其所在的行
+#[derive(Debug, PartialEq)]
+pub(crate) struct Property {
+    pub(crate) key: String,
+    pub(crate) value: String,
+    pub(crate) line: usize,
+}
+
+/// 逻辑行中的字符及其原始位置 `(字符, 行, 列)`
+type Located = (char, usize, usize);
+
+const BLANK: [char; 3] = [' ', '\t', '\x0c'];
+
+/// 按 `java.util.Properties::load` 的规则解析
+///
+/// 支持 `#` / `!` 注释、`=` / `:` / 空白分隔符、行尾 `\` 续行和 `\t`、`\n`、`\uXXXX` 等转义。
+pub(crate) fn parse(content: &str) -> Result<Vec<Property>, SyntaxError> {
+    let mut properties = Vec::new();
+    let mut lines = content.lines().enumerate();
+    while let Some((index, raw)) = lines.next() {
+        let text = raw.trim_start_matches(BLANK);
+        if text.is_empty() || text.starts_with(['#', '!']) {
+            continue;
+        }
+        // 拼接续行，续行的前导空白不计入值
+        let mut chars: Vec<Located> = Vec::new();
+        let (mut line, mut column, mut text) = (index + 1, leading(raw, text), text);
+        loop {
+            let trailing = text.chars().rev().take_while(|c| *c == '\\').count();
+            let continued = trailing % 2 == 1;
+            let body = if continued {
+                &text[..text.len() - 1]
+            } else {
+                text
+            };
+            chars.extend(
+                body.chars()
+                    .enumerate()
+                    .map(|(offset, c)| (c, line, column + offset)),
+            );
+            if !continued {
+                break;
+            }
+            let Some((index, raw)) = lines.next() else {
+                break;
+            };
+            let next = raw.trim_start_matches(BLANK);
+            (line, column, text) = (index + 1, leading(raw, next), next);
+        }
+        let (key, value) = split_entry(&chars);
+        properties.push(Property {
+            key: unescape(key)?,
+            value: unescape(value)?,
+            line: index + 1,
+        });
+    }
+    Ok(properties)
+}
+
+/// 行首空白之后第一个字符的列号
+fn leading(raw: &str, text: &str) -> usize {
+    raw.chars().count() - text.chars().count() + 1
+}
+
+/// 键止于第一个未转义的 `=`、`:` 或空白；分隔符两侧的空白不属于键值
+fn split_entry(chars: &[Located]) -> (&[Located], &[Located]) {
+    let mut end = 0;
+    while end < chars.len() {
+        match chars[end].0 {
+            '\\' => end += 2,
+            '=' | ':' => break,
+            c if BLANK.contains(&c) => break,
+            _ => end += 1,
+        }
+    }
+    let end = end.min(chars.len());
+    let mut start = end;
+    while start < chars.len() && BLANK.contains(&chars[start].0) {
+        start += 1;
+    }
+    if start < chars.len() && matches!(chars[start].0, '=' | ':') {
+        start += 1;
+        while start < chars.len() && BLANK.contains(&chars[start].0) {
+            start += 1;
+        }
+    }
+    (&chars[..end], &chars[start..])
+}
+
+fn unescape(chars: &[Located]) -> Result<String, SyntaxError> {
+    let mut out = String::new();
+    let mut iter = chars.iter().peekable();
+    while let Some(&(c, line, column)) = iter.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let Some(&(escaped, ..)) = iter.next() else {
+            break;
+        };
+        match escaped {
+            't' => out.push('\t'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            'f' => out.push('\x0c'),
+            'u' => {
+                let invalid = || SyntaxError {
+                    line,
+                    column,
+                    message: "invalid \\uXXXX escape".to_string(),
+                };
+                let mut code = hex4(&mut iter).ok_or_else(invalid)?;
+                // UTF-16 代理对需要紧跟的第二个 `\uXXXX`
+                if (0xD800..0xDC00).contains(&code) {
+                    let low = match (iter.next(), iter.next()) {
+                        (Some(('\\', ..)), Some(('u', ..))) => hex4(&mut iter),
+                        _ => None,
+                    }
+                    .filter(|low| (0xDC00..0xE000).contains(low))
+                    .ok_or_else(invalid)?;
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                }
+                out.push(char::from_u32(code).ok_or_else(invalid)?);
+            }
+            other => out.push(other),
+        }
+    }
+    Ok(out)
+}
+
+fn hex4<'a>(iter: &mut impl Iterator<Item = &'a Located>) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + iter.next()?.0.to_digit(16)?;
+    }
+    Some(code)
+}
+
+/// 点分键展开为嵌套映射
+///
+/// 下标键（`hosts.0`）保留为映射，目标类型为序列时由宽松反序列化按下标读作数组。
+///
+/// 同一个键既是值又是映射（`a=1` 与 `a.b=2`）时报错；重复的键以后出现的为准。
+pub(crate) fn nest(properties: Vec<Property>) -> Result<Value, SyntaxError> {
+    let mut root = Map::new();
+    for Property { key, value, line } in properties {
+        let conflict = || SyntaxError {
+            line,
+            column: 1,
+            message: format!("key `{key}` conflicts with another key"),
+        };
+        let mut segments: Vec<&str> = key.split('.').collect();
+        let last = segments.pop().unwrap_or_default();
+        let mut node = &mut root;
+        for segment in segments {
+            let child = node
+                .entry(segment)
+                .or_insert_with(|| Value::Object(Map::new()));
+            node = child.as_object_mut().ok_or_else(conflict)?;
+        }
+        if node.get(last).is_some_and(Value::is_object) {
+            return Err(conflict());
+        }
+        node.insert(last.to_string(), Value::String(value));
+    }
+    Ok(Value::Object(root))
+}
+
+/// 转义键中的分隔符、注释符和空白
+pub(crate) fn escape_key(key: &str) -> String {
+    let mut out = String::new();
+    for (index, c) in key.chars().enumerate() {
+        match c {
+            ' ' | '=' | ':' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '#' | '!' if index == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => push_control(&mut out, c),
+        }
+    }
+    out
+}
+
+/// 转义值中的反斜杠、控制字符和前导空白
+pub(crate) fn escape_value(value: &str) -> String {
+    let mut out = String::new();
+    let mut leading = true;
+    for c in value.chars() {
+        match c {
+            ' ' if leading => out.push_str("\\ "),
+            '\\' => out.push_str("\\\\"),
+            _ => push_control(&mut out, c),
+        }
+        leading &= c == ' ';
+    }
+    out
+}
+
+fn push_control(out: &mut String, c: char) {
+    match c {
+        '\t' => out.push_str("\\t"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\x0c' => out.push_str("\\f"),
+        c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+        c => out.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pairs(content: &str) -> Vec<(String, String)> {
+        parse(content)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.key, p.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_separators_and_comments() {
+        let content =
+            "# comment\n! also comment\n\na=1\nb : 2\nc 3\n  d=\nkey\\ with\\=sep = v=x\n";
+        assert_eq!(
+            pairs(content),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+                ("c".to_string(), "3".to_string()),
+                ("d".to_string(), "".to_string()),
+                ("key with=sep".to_string(), "v=x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_continuation_and_escapes() {
+        let content = "fruits = apple, \\\n    banana, \\\n    pear\npath=c:\\\\dir\\\\\ntab=a\\tb\\nc\nsnow=\\u2603 \\uD83D\\uDE00\n";
+        assert_eq!(
+            pairs(content),
+            vec![
+                ("fruits".to_string(), "apple, banana, pear".to_string()),
+                ("path".to_string(), "c:\\dir\\".to_string()),
+                ("tab".to_string(), "a\tb\nc".to_string()),
+                ("snow".to_string(), "\u{2603} \u{1F600}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_unicode_escape() {
+        let err = parse("a=1\nb = x\\u12G4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
+        assert_eq!(err.message, "invalid \\uXXXX escape");
+    }
+
+    #[test]
+    fn test_nest_dotted_keys() {
+        let tree = nest(parse("a.b=1\na.c=x\nlist.1=second\nlist.0=first\nsparse.1=y\n").unwrap())
+            .unwrap();
+        assert_eq!(
+            tree,
+            json!({
+                "a": {"b": "1", "c": "x"},
+                "list": {"1": "second", "0": "first"},
+                "sparse": {"1": "y"}
+            })
+        );
+
+        let err = nest(parse("a=1\na.b=2\n").unwrap()).unwrap_err();
+        assert_eq!(err.line, 2);
+        let err = nest(parse("a.b=2\na=1\n").unwrap()).unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let key = "#odd key=:";
+        let value = "  lead\\trail\ttab\nline";
+        let line = format!("{} = {}", escape_key(key), escape_value(value));
+        assert_eq!(pairs(&line), vec![(key.to_string(), value.to_string())]);
+    }
+}
@@ -0,0 +1,962 @@
+//! 保存时的输出格式选项
+
+/// TOML 中嵌套表的写法
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TomlTables {
+    /// `[section]` 形式的独立表
+    #[default]
+    Sections,
+    /// 顶层键下的 `key = { ... }` 内联表，键按字母序输出
+    Inline,
+}
+
+/// 保存选项，供 `save_*_with` / `save_conf_with` 按次调用时指定
+///
+/// 默认输出便于人工编辑和 diff：美化格式、2 空格缩进、以换行结尾、保持字段顺序。
+/// 缩进宽度只作用于 JSON、RON 和 HCL；YAML 始终为 2 空格块格式，INI 不区分美化与紧凑；
+/// RON 不排序键（借助值树排序会丢失枚举变体名）；dotenv 只能保存平铺的字段；
+/// properties 把嵌套字段展开为点分键；MessagePack 和 CBOR 只使用 `sort_keys`。
+#[derive(Clone, Debug)]
+pub struct SaveOptions {
+    pretty: bool,
+    indent: usize,
+    trailing_newline: bool,
+    sort_keys: bool,
+    toml_tables: TomlTables,
+    #[cfg(feature = "schema")]
+    schema: Option<String>,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            pretty: true,
+            indent: 2,
+            trailing_newline: true,
+            sort_keys: false,
+            toml_tables: TomlTables::default(),
+            #[cfg(feature = "schema")]
+            schema: None,
+        }
+    }
+}
+
+impl SaveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 紧凑输出：JSON 单行、TOML 数组不换行
+    pub fn compact() -> Self {
+        Self::default().with_pretty(false)
+    }
+
+    pub fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    pub fn pretty(&self) -> bool {
+        self.pretty
+    }
+
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    pub fn trailing_newline(&self) -> bool {
+        self.trailing_newline
+    }
+
+    /// 按键名排序映射（递归），使输出与字段声明顺序无关
+    pub fn with_sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    pub fn sort_keys(&self) -> bool {
+        self.sort_keys
+    }
+
+    pub fn with_toml_tables(mut self, toml_tables: TomlTables) -> Self {
+        self.toml_tables = toml_tables;
+        self
+    }
+
+    pub fn toml_tables(&self) -> TomlTables {
+        self.toml_tables
+    }
+
+    /// 写入编辑器可识别的 schema 关联：JSON/JSON5 顶层的 `$schema` 字段、
+    /// YAML 开头的 `# yaml-language-server: $schema=` 注释、TOML 开头的 Taplo `#:schema` 指令
+    #[cfg(feature = "schema")]
+    pub fn with_schema(mut self, url: impl Into<String>) -> Self {
+        self.schema = Some(url.into());
+        self
+    }
+
+    #[cfg(feature = "schema")]
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// 按选项补上或去掉结尾换行
+    #[allow(dead_code)]
+    pub(crate) fn finish(&self, mut content: String) -> String {
+        if self.trailing_newline {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+        } else if content.ends_with('\n') {
+            content.pop();
+        }
+        content
+    }
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+pub(crate) fn to_json_string<T>(value: &T, options: &SaveOptions) -> serde_json::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    #[cfg(feature = "schema")]
+    if let Some(schema) = options.schema() {
+        return write_json(&SchemaTagged { schema, value }, options);
+    }
+    write_json(value, options)
+}
+
+/// 顶层映射最前面加上 `$schema` 字段
+#[cfg(all(feature = "schema", any(feature = "json", feature = "json5")))]
+#[derive(serde_derive::Serialize)]
+struct SchemaTagged<'a, T: ?Sized> {
+    #[serde(rename = "$schema")]
+    schema: &'a str,
+    #[serde(flatten)]
+    value: &'a T,
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+fn write_json<T>(value: &T, options: &SaveOptions) -> serde_json::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    fn write<S: serde::Serialize + ?Sized>(
+        value: &S,
+        options: &SaveOptions,
+    ) -> serde_json::Result<String> {
+        let mut buf = Vec::new();
+        if options.pretty() {
+            let indent = " ".repeat(options.indent());
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            value.serialize(&mut serde_json::Serializer::with_formatter(
+                &mut buf, formatter,
+            ))?;
+        } else {
+            value.serialize(&mut serde_json::Serializer::new(&mut buf))?;
+        }
+        // serde_json 只输出合法的 UTF-8
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    let content = if options.sort_keys() {
+        write(&sort_json(serde_json::to_value(value)?), options)?
+    } else {
+        write(value, options)?
+    };
+    Ok(options.finish(content))
+}
+
+#[cfg(any(
+    feature = "json",
+    feature = "json5",
+    feature = "hcl",
+    feature = "msgpack",
+    feature = "cbor"
+))]
+fn sort_json(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_json(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(list) => Value::Array(list.into_iter().map(sort_json).collect()),
+        other => other,
+    }
+}
+
+#[cfg(feature = "toml")]
+pub(crate) fn to_toml_string<T>(
+    value: &T,
+    options: &SaveOptions,
+) -> Result<String, toml::ser::Error>
+where
+    T: serde::Serialize + ?Sized,
+{
+    let content = match options.toml_tables() {
+        TomlTables::Inline => {
+            let table = sort_toml_table(toml::Table::try_from(value)?);
+            let mut content = String::new();
+            for (key, item) in &table {
+                content.push_str(&format!("{} = {}\n", toml_key(key)?, toml_inline(item)?));
+            }
+            content
+        }
+        TomlTables::Sections if options.sort_keys() => {
+            let table = sort_toml_table(toml::Table::try_from(value)?);
+            write_toml(&table, options)?
+        }
+        TomlTables::Sections => write_toml(value, options)?,
+    };
+    #[cfg(feature = "schema")]
+    let content = prepend_schema(content, "#:schema ", options);
+    Ok(options.finish(content))
+}
+
+#[cfg(feature = "toml")]
+fn write_toml<S: serde::Serialize + ?Sized>(
+    value: &S,
+    options: &SaveOptions,
+) -> Result<String, toml::ser::Error> {
+    if options.pretty() {
+        toml::to_string_pretty(value)
+    } else {
+        toml::to_string(value)
+    }
+}
+
+/// 裸键保持原样，其余键写成带引号的字符串
+#[cfg(feature = "toml")]
+fn toml_key(key: &str) -> Result<String, toml::ser::Error> {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        Ok(key.to_string())
+    } else {
+        toml_inline(&toml::Value::String(key.to_string()))
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_inline(value: &toml::Value) -> Result<String, toml::ser::Error> {
+    use serde::Serialize;
+
+    let mut out = String::new();
+    value.serialize(toml::ser::ValueSerializer::new(&mut out))?;
+    Ok(out)
+}
+
+#[cfg(feature = "toml")]
+fn sort_toml_table(table: toml::Table) -> toml::Table {
+    let mut entries: Vec<_> = table.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+        .into_iter()
+        .map(|(k, v)| (k, sort_toml(v)))
+        .collect()
+}
+
+#[cfg(feature = "toml")]
+fn sort_toml(value: toml::Value) -> toml::Value {
+    use toml::Value;
+
+    match value {
+        Value::Table(table) => Value::Table(sort_toml_table(table)),
+        Value::Array(list) => Value::Array(list.into_iter().map(sort_toml).collect()),
+        other => other,
+    }
+}
+
+#[cfg(feature = "yaml")]
+pub(crate) fn to_yaml_string<T>(value: &T, options: &SaveOptions) -> serde_yaml::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    let content = if options.sort_keys() {
+        serde_yaml::to_string(&sort_yaml(serde_yaml::to_value(value)?))?
+    } else {
+        serde_yaml::to_string(value)?
+    };
+    #[cfg(feature = "schema")]
+    let content = prepend_schema(content, "# yaml-language-server: $schema=", options);
+    Ok(options.finish(content))
+}
+
+/// 在文本开头写入 schema 关联注释
+#[cfg(all(feature = "schema", any(feature = "yaml", feature = "toml")))]
+fn prepend_schema(content: String, directive: &str, options: &SaveOptions) -> String {
+    match options.schema() {
+        Some(url) => format!("{directive}{url}\n{content}"),
+        None => content,
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn sort_yaml(value: serde_yaml::Value) -> serde_yaml::Value {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Mapping(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            Value::Mapping(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_yaml(v)))
+                    .collect(),
+            )
+        }
+        Value::Sequence(list) => Value::Sequence(list.into_iter().map(sort_yaml).collect()),
+        Value::Tagged(mut tagged) => {
+            tagged.value = sort_yaml(tagged.value);
+            Value::Tagged(tagged)
+        }
+        other => other,
+    }
+}
+
+/// 嵌套结构写成 `[a.b]` 节，数组写成重复的 `key[]` 行；`null` 字段和空数组不输出
+#[cfg(feature = "ini")]
+pub(crate) fn to_ini_string<T>(value: &T, options: &SaveOptions) -> serde_json::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+    use serde_json::Value;
+
+    fn scalar(key: &str, node: Ordered) -> serde_json::Result<Option<String>> {
+        match node {
+            Ordered::Scalar(Value::Null) => Ok(None),
+            Ordered::Scalar(Value::String(text)) => Ok(Some(crate::ini::quote_value(&text))),
+            Ordered::Scalar(other) => Ok(Some(other.to_string())),
+            _ => Err(serde_json::Error::custom(format!(
+                "ini arrays can only hold scalar values, `{key}` is nested"
+            ))),
+        }
+    }
+
+    fn section(
+        out: &mut String,
+        path: &mut Vec<String>,
+        entries: Vec<(String, Ordered)>,
+    ) -> serde_json::Result<()> {
+        let (tables, leaves): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|(_, node)| matches!(node, Ordered::Map(_)));
+        // 只含子节的节不单独写节头
+        if !path.is_empty() && (!leaves.is_empty() || tables.is_empty()) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{}]\n", path.join(".")));
+        }
+        for (key, node) in leaves {
+            if !crate::ini::is_plain_key(&key) {
+                return Err(serde_json::Error::custom(format!(
+                    "`{key}` cannot be written as an ini key"
+                )));
+            }
+            match node {
+                // 空数组写成空值，加载时读回空数组
+                Ordered::List(items) if items.is_empty() => out.push_str(&format!("{key}=\n")),
+                Ordered::List(items) => {
+                    for item in items {
+                        let text = scalar(&key, item)?.ok_or_else(|| {
+                            serde_json::Error::custom(format!(
+                                "ini arrays cannot hold null, `{key}`"
+                            ))
+                        })?;
+                        out.push_str(&format!("{key}[]={text}\n"));
+                    }
+                }
+                node => {
+                    if let Some(text) = scalar(&key, node)? {
+                        out.push_str(&format!("{key}={text}\n"));
+                    }
+                }
+            }
+        }
+        for (key, node) in tables {
+            if !crate::ini::is_plain_key(&key) || key.contains('.') {
+                return Err(serde_json::Error::custom(format!(
+                    "`{key}` cannot be written as an ini section name"
+                )));
+            }
+            if let Ordered::Map(entries) = node {
+                path.push(key);
+                section(out, path, entries)?;
+                path.pop();
+            }
+        }
+        Ok(())
+    }
+
+    let mut tree = Ordered::from_serialize(value)?;
+    if options.sort_keys() {
+        tree.sort_keys();
+    }
+    let Ordered::Map(entries) = tree else {
+        return Err(serde_json::Error::custom(
+            "ini output requires a struct or map at the top level",
+        ));
+    };
+    let mut content = String::new();
+    section(&mut content, &mut Vec::new(), entries)?;
+    Ok(options.finish(content))
+}
+
+#[cfg(feature = "ron")]
+pub(crate) fn to_ron_string<T>(value: &T, options: &SaveOptions) -> ron::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    let content = if options.pretty() {
+        let config = ron::ser::PrettyConfig::new().indentor(" ".repeat(options.indent()));
+        ron::ser::to_string_pretty(value, config)?
+    } else {
+        ron::to_string(value)?
+    };
+    Ok(options.finish(content))
+}
+
+/// 紧凑模式下数组和对象写在一行内；键名尽量写成标识符
+#[cfg(feature = "hcl")]
+pub(crate) fn to_hcl_string<T>(value: &T, options: &SaveOptions) -> hcl::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+
+    let indent = " ".repeat(options.indent());
+    let mut buf = Vec::new();
+    let formatter = hcl::format::Formatter::builder()
+        .indent(indent.as_bytes())
+        .compact(!options.pretty())
+        .prefer_ident_keys(true)
+        .build(&mut buf);
+    let mut serializer = hcl::ser::Serializer::with_formatter(formatter);
+    if options.sort_keys() {
+        let tree = serde_json::to_value(value).map_err(hcl::Error::custom)?;
+        serializer.serialize(&sort_json(tree))?;
+    } else {
+        serializer.serialize(value)?;
+    }
+    // 格式化器只输出合法的 UTF-8
+    Ok(options.finish(String::from_utf8_lossy(&buf).into_owned()))
+}
+
+/// 键名转为大写；嵌套的映射和数组无法表示，返回错误；`null` 字段不输出
+#[cfg(feature = "dotenv")]
+pub(crate) fn to_dotenv_string<T>(value: &T, options: &SaveOptions) -> serde_json::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+    use serde_json::Value;
+
+    let Ordered::Map(mut entries) = Ordered::from_serialize(value)? else {
+        return Err(serde_json::Error::custom(
+            "dotenv output requires a struct or map at the top level",
+        ));
+    };
+    if options.sort_keys() {
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    let mut content = String::new();
+    for (key, value) in entries {
+        let text = match value {
+            Ordered::Scalar(Value::Null) => continue,
+            Ordered::Scalar(Value::String(text)) => dotenv_quote(&text),
+            Ordered::Scalar(scalar) => scalar.to_string(),
+            Ordered::Map(_) | Ordered::List(_) => {
+                return Err(serde_json::Error::custom(format!(
+                    "dotenv only supports flat values, `{key}` is nested"
+                )));
+            }
+        };
+        content.push_str(&format!("{}={text}\n", key.to_ascii_uppercase()));
+    }
+    Ok(options.finish(content))
+}
+
+/// 嵌套结构展开为点分键；数组元素以下标为键（`hosts.0`）；`null` 字段不输出
+#[cfg(feature = "properties")]
+pub(crate) fn to_properties_string<T>(
+    value: &T,
+    options: &SaveOptions,
+) -> serde_json::Result<String>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+    use serde_json::Value;
+
+    fn flatten(prefix: &str, node: Ordered, out: &mut Vec<(String, String)>) {
+        let join = |key: &str| match prefix {
+            "" => key.to_string(),
+            _ => format!("{prefix}.{key}"),
+        };
+        match node {
+            Ordered::Scalar(Value::Null) => {}
+            Ordered::Scalar(Value::String(text)) => out.push((prefix.to_string(), text)),
+            Ordered::Scalar(scalar) => out.push((prefix.to_string(), scalar.to_string())),
+            Ordered::Map(entries) => {
+                let start = out.len();
+                for (key, child) in entries {
+                    flatten(&join(&key), child, out);
+                }
+                // 空列表、空映射写成空值，加载时读回空容器
+                if out.len() == start && !prefix.is_empty() {
+                    out.push((prefix.to_string(), String::new()));
+                }
+            }
+            Ordered::List(items) => {
+                let start = out.len();
+                for (index, child) in items.into_iter().enumerate() {
+                    flatten(&join(&index.to_string()), child, out);
+                }
+                if out.len() == start && !prefix.is_empty() {
+                    out.push((prefix.to_string(), String::new()));
+                }
+            }
+        }
+    }
+
+    let mut tree = Ordered::from_serialize(value)?;
+    if !matches!(tree, Ordered::Map(_)) {
+        return Err(serde_json::Error::custom(
+            "properties output requires a struct or map at the top level",
+        ));
+    }
+    if options.sort_keys() {
+        tree.sort_keys();
+    }
+    let mut entries = Vec::new();
+    flatten("", tree, &mut entries);
+    let separator = if options.pretty() { " = " } else { "=" };
+    let mut content = String::new();
+    for (key, value) in entries {
+        content.push_str(&crate::properties::escape_key(&key));
+        content.push_str(separator);
+        content.push_str(&crate::properties::escape_value(&value));
+        content.push('\n');
+    }
+    Ok(options.finish(content))
+}
+
+/// 结构体按字段名写成 map，便于字段增减后继续读取旧缓存
+#[cfg(feature = "msgpack")]
+pub(crate) fn to_msgpack_vec<T>(
+    value: &T,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, rmp_serde::encode::Error>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+
+    if options.sort_keys() {
+        let tree = serde_json::to_value(value).map_err(rmp_serde::encode::Error::custom)?;
+        rmp_serde::to_vec_named(&sort_json(tree))
+    } else {
+        rmp_serde::to_vec_named(value)
+    }
+}
+
+#[cfg(feature = "cbor")]
+pub(crate) fn to_cbor_vec<T>(
+    value: &T,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>>
+where
+    T: serde::Serialize + ?Sized,
+{
+    use serde::ser::Error;
+
+    let mut buf = Vec::new();
+    if options.sort_keys() {
+        let tree = serde_json::to_value(value).map_err(ciborium::ser::Error::custom)?;
+        ciborium::ser::into_writer(&sort_json(tree), &mut buf)?;
+    } else {
+        ciborium::ser::into_writer(value, &mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// 保持映射键顺序的值树（`serde_json::Value` 的映射按键名排序）
+#[cfg(any(feature = "ini", feature = "dotenv", feature = "properties"))]
+enum Ordered {
+    Scalar(serde_json::Value),
+    Map(Vec<(String, Ordered)>),
+    #[cfg_attr(not(any(feature = "ini", feature = "properties")), allow(dead_code))]
+    List(Vec<Ordered>),
+}
+
+#[cfg(any(feature = "ini", feature = "dotenv", feature = "properties"))]
+impl Ordered {
+    /// 经由 JSON 文本读回，保持字段声明顺序
+    fn from_serialize<T: serde::Serialize + ?Sized>(value: &T) -> serde_json::Result<Self> {
+        serde_json::from_str(&serde_json::to_string(value)?)
+    }
+
+    /// 递归按键名排序映射，数组保持原有顺序
+    #[cfg(any(feature = "ini", feature = "properties"))]
+    fn sort_keys(&mut self) {
+        match self {
+            Ordered::Scalar(_) => {}
+            Ordered::Map(entries) => {
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries.iter_mut().for_each(|(_, child)| child.sort_keys());
+            }
+            Ordered::List(items) => items.iter_mut().for_each(Ordered::sort_keys),
+        }
+    }
+}
+
+#[cfg(any(feature = "ini", feature = "dotenv", feature = "properties"))]
+impl<'de> serde::Deserialize<'de> for Ordered {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{MapAccess, SeqAccess, Visitor};
+        use serde_json::Value;
+
+        struct OrderedVisitor;
+
+        impl<'de> Visitor<'de> for OrderedVisitor {
+            type Value = Ordered;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::from(v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::from(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::from(v)))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::from(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::from(v)))
+            }
+
+            fn visit_unit<E>(self) -> Result<Ordered, E> {
+                Ok(Ordered::Scalar(Value::Null))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Ordered, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Ordered::List(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Ordered, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Ordered::Map(entries))
+            }
+        }
+
+        deserializer.deserialize_any(OrderedVisitor)
+    }
+}
+
+/// 含空白、引号、`#`、`$` 等字符的值写成双引号字符串
+#[cfg(feature = "dotenv")]
+fn dotenv_quote(text: &str) -> String {
+    let bare = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-./:@,+%".contains(c));
+    if bare {
+        return text.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' | '"' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(
+        feature = "json",
+        feature = "toml",
+        feature = "yaml",
+        feature = "dotenv",
+        feature = "hcl",
+        feature = "properties"
+    ))]
+    use serde_derive::Serialize;
+
+    #[cfg(any(
+        feature = "json",
+        feature = "toml",
+        feature = "yaml",
+        feature = "hcl",
+        feature = "properties"
+    ))]
+    #[derive(Serialize)]
+    struct Sample {
+        name: String,
+        ports: Vec<u16>,
+        database: Database,
+    }
+
+    #[cfg(any(
+        feature = "json",
+        feature = "toml",
+        feature = "yaml",
+        feature = "hcl",
+        feature = "properties"
+    ))]
+    #[derive(Serialize)]
+    struct Database {
+        url: String,
+        pool: u32,
+    }
+
+    #[cfg(any(
+        feature = "json",
+        feature = "toml",
+        feature = "yaml",
+        feature = "hcl",
+        feature = "properties"
+    ))]
+    fn sample() -> Sample {
+        Sample {
+            name: "svc".to_string(),
+            ports: vec![80, 443],
+            database: Database {
+                url: "pg://local".to_string(),
+                pool: 4,
+            },
+        }
+    }
+
+    #[test]
+    fn test_finish_trailing_newline() {
+        let options = SaveOptions::default();
+        assert_eq!(options.finish("a".to_string()), "a\n");
+        assert_eq!(options.finish("a\n".to_string()), "a\n");
+        let options = options.with_trailing_newline(false);
+        assert_eq!(options.finish("a\n".to_string()), "a");
+        assert_eq!(options.finish("a".to_string()), "a");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_string_options() {
+        let pretty = to_json_string(&sample(), &SaveOptions::default()).unwrap();
+        assert_eq!(
+            pretty,
+            "{\n  \"name\": \"svc\",\n  \"ports\": [\n    80,\n    443\n  ],\n  \"database\": {\n    \"url\": \"pg://local\",\n    \"pool\": 4\n  }\n}\n"
+        );
+
+        let compact = to_json_string(&sample(), &SaveOptions::compact()).unwrap();
+        assert_eq!(
+            compact,
+            "{\"name\":\"svc\",\"ports\":[80,443],\"database\":{\"url\":\"pg://local\",\"pool\":4}}\n"
+        );
+
+        let options = SaveOptions::new()
+            .with_indent(4)
+            .with_sort_keys(true)
+            .with_trailing_newline(false);
+        let sorted = to_json_string(&sample(), &options).unwrap();
+        assert!(sorted.starts_with("{\n    \"database\": {\n        \"pool\": 4,"));
+        assert!(sorted.ends_with('}'));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_to_toml_string_tables() {
+        let sections = to_toml_string(&sample(), &SaveOptions::default()).unwrap();
+        assert_eq!(
+            sections,
+            "name = \"svc\"\nports = [\n    80,\n    443,\n]\n\n[database]\nurl = \"pg://local\"\npool = 4\n"
+        );
+
+        let options = SaveOptions::compact().with_toml_tables(TomlTables::Inline);
+        let inline = to_toml_string(&sample(), &options).unwrap();
+        assert_eq!(
+            inline,
+            "database = { pool = 4, url = \"pg://local\" }\nname = \"svc\"\nports = [80, 443]\n"
+        );
+        let parsed: toml::Table = toml::from_str(&inline).unwrap();
+        assert_eq!(parsed["database"]["pool"].as_integer(), Some(4));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_key_quoting() {
+        assert_eq!(toml_key("bare_key-1").unwrap(), "bare_key-1");
+        assert_eq!(toml_key("a.b").unwrap(), "\"a.b\"");
+        assert_eq!(toml_key("").unwrap(), "\"\"");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_to_yaml_string_sort_keys() {
+        let options = SaveOptions::new().with_sort_keys(true);
+        let sorted = to_yaml_string(&sample(), &options).unwrap();
+        assert_eq!(
+            sorted,
+            "database:\n  pool: 4\n  url: pg://local\nname: svc\nports:\n- 80\n- 443\n"
+        );
+        let unsorted = to_yaml_string(&sample(), &SaveOptions::default()).unwrap();
+        assert!(unsorted.starts_with("name: svc\n"));
+    }
+
+    #[cfg(feature = "hcl")]
+    #[test]
+    fn test_to_hcl_string_options() {
+        let pretty = to_hcl_string(&sample(), &SaveOptions::default()).unwrap();
+        assert_eq!(
+            pretty,
+            "name = \"svc\"\nports = [\n  80,\n  443\n]\ndatabase = {\n  url = \"pg://local\"\n  pool = 4\n}\n"
+        );
+
+        let options = SaveOptions::compact()
+            .with_sort_keys(true)
+            .with_trailing_newline(false);
+        let compact = to_hcl_string(&sample(), &options).unwrap();
+        assert_eq!(
+            compact,
+            "database = { pool = 4, url = \"pg://local\" }\nname = \"svc\"\nports = [80, 443]"
+        );
+    }
+
+    #[cfg(feature = "dotenv")]
+    #[test]
+    fn test_to_dotenv_string() {
+        #[derive(Serialize)]
+        struct Flat {
+            name: String,
+            port: u16,
+            debug: bool,
+            token: Option<String>,
+            motd: String,
+        }
+
+        let flat = Flat {
+            name: "svc".to_string(),
+            port: 8080,
+            debug: false,
+            token: None,
+            motd: "hi \"$USER\"\nbye".to_string(),
+        };
+        let content = to_dotenv_string(&flat, &SaveOptions::new().with_sort_keys(true)).unwrap();
+        assert_eq!(
+            content,
+            "DEBUG=false\nMOTD=\"hi \\\"\\$USER\\\"\\nbye\"\nNAME=svc\nPORT=8080\n"
+        );
+        let dict = crate::dotenv::DotenvSource::new().parse(&content).unwrap();
+        assert_eq!(
+            dict.get("MOTD").map(|v| v.to_string()),
+            Some(flat.motd.clone())
+        );
+
+        let err = to_dotenv_string(&serde_json::json!({"db": {"port": 1}}), &SaveOptions::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("`db` is nested"));
+    }
+
+    #[cfg(feature = "properties")]
+    #[test]
+    fn test_to_properties_string() {
+        let pretty = to_properties_string(&sample(), &SaveOptions::default()).unwrap();
+        assert_eq!(
+            pretty,
+            "name = svc\nports.0 = 80\nports.1 = 443\ndatabase.url = pg://local\ndatabase.pool = 4\n"
+        );
+
+        let options = SaveOptions::compact().with_sort_keys(true);
+        let compact = to_properties_string(&sample(), &options).unwrap();
+        assert_eq!(
+            compact,
+            "database.pool=4\ndatabase.url=pg://local\nname=svc\nports.0=80\nports.1=443\n"
+        );
+
+        let escaped = serde_json::json!({"a key": " lead\\tab\t", "none": null});
+        let content = to_properties_string(&escaped, &SaveOptions::default()).unwrap();
+        assert_eq!(content, "a\\ key = \\ lead\\\\tab\\t\n");
+
+        let empty = serde_json::json!({"list": [], "map": {"none": null}});
+        let content = to_properties_string(&empty, &SaveOptions::compact()).unwrap();
+        assert_eq!(content, "list=\nmap=\n");
+
+        let err = to_properties_string(&[1, 2], &SaveOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("top level"));
+    }
+
+    #[cfg(feature = "ini")]
+    #[test]
+    fn test_to_ini_string() {
+        let value = serde_json::json!({
+            "name": "svc",
+            "tags": ["a", "b"],
+            "db": {"url": " spaced", "pool": {"size": 4}},
+            "empty": {},
+            "none": null,
+            "hosts": []
+        });
+        let content = to_ini_string(&value, &SaveOptions::new().with_sort_keys(true)).unwrap();
+        assert_eq!(
+            content,
+            "hosts=\nname=svc\ntags[]=a\ntags[]=b\n\n[db]\nurl=\" spaced\"\n\n[db.pool]\nsize=4\n\n[empty]\n"
+        );
+
+        let err = to_ini_string(
+            &serde_json::json!({"list": [{"a": 1}]}),
+            &SaveOptions::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("`list` is nested"));
+        let err =
+            to_ini_string(&serde_json::json!({"a.b": {"c": 1}}), &SaveOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("section name"));
+    }
+}
@@ -5,21 +5,32 @@ use orion_variate::EnvDict;
 pub use serde_derive::{Deserialize, Serialize};
 use std::path::Path;
 
-use crate::error::OrionConfResult;
+use crate::env::EnvOptions;
+use crate::error::{ConfIOReason, OrionConfResult};
+use crate::format::ConfFormat;
+use crate::save::SaveOptions;
+use crate::validate::Violation;
 
 // 核心持久化 trait - 不依赖任何特定格式
+// path 为目录，name 覆盖 PersistName 提供的默认文件名
 pub trait FilePersist<T> {
     fn save_to(&self, path: &Path, name: Option<String>) -> OrionConfResult<()>;
     fn load_from(path: &Path) -> OrionConfResult<T>;
 }
 
-// 通用配置 trait - 默认行为基于可用特性
+// 类型级默认文件名 - 不含扩展名时按特性优先级补全扩展名
+pub trait PersistName {
+    const PERSIST_NAME: &'static str;
+}
+
+// 通用配置 trait - 按扩展名选择格式，未知扩展名时基于可用特性
 pub trait ConfigIO<T>
 where
     T: serde::de::DeserializeOwned + serde::Serialize,
 {
     fn load_conf(path: &Path) -> OrionConfResult<T>;
     fn save_conf(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_conf_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
 }
 
 // 通用配置 trait - 默认行为基于可用特性
@@ -29,6 +40,7 @@ where
 {
     // 推荐方法名
     fn env_load_conf(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_conf_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
 }
 
 // 格式特定的 trait - 每个都使用条件编译
@@ -40,6 +52,7 @@ where
 {
     fn load_ini(path: &Path) -> OrionConfResult<T>;
     fn save_ini(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_ini_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
 }
 
 #[cfg(feature = "ini")]
@@ -49,6 +62,12 @@ where
 {
     fn env_load_ini(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
     fn env_parse_ini(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_ini_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_ini_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
 }
 
 #[cfg(feature = "json")]
@@ -58,6 +77,7 @@ where
 {
     fn load_json(path: &Path) -> OrionConfResult<T>;
     fn save_json(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_json_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
 }
 
 #[cfg(feature = "json")]
@@ -67,6 +87,177 @@ where
 {
     fn env_load_json(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
     fn env_parse_json(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_json_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_json_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+#[cfg(feature = "json5")]
+pub trait Json5IO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_json5(path: &Path) -> OrionConfResult<T>;
+    fn save_json5(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_json5_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "json5")]
+pub trait EnvJson5Load<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn env_load_json5(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_json5(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_json5_with(path: &Path, dict: &EnvDict, options: &EnvOptions)
+    -> OrionConfResult<T>;
+    fn env_parse_json5_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+#[cfg(feature = "ron")]
+pub trait RonIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_ron(path: &Path) -> OrionConfResult<T>;
+    fn save_ron(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_ron_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "ron")]
+pub trait EnvRonLoad<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn env_load_ron(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_ron(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_ron_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_ron_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+#[cfg(feature = "dotenv")]
+pub trait DotenvIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_dotenv(path: &Path) -> OrionConfResult<T>;
+    fn save_dotenv(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_dotenv_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "dotenv")]
+pub trait EnvDotenvLoad<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn env_load_dotenv(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_dotenv(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_dotenv_with(
+        path: &Path,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+    fn env_parse_dotenv_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+#[cfg(feature = "hcl")]
+pub trait HclIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_hcl(path: &Path) -> OrionConfResult<T>;
+    fn save_hcl(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_hcl_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "hcl")]
+pub trait EnvHclLoad<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn env_load_hcl(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_hcl(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_hcl_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_hcl_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+#[cfg(feature = "properties")]
+pub trait PropertiesIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_properties(path: &Path) -> OrionConfResult<T>;
+    fn save_properties(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_properties_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "properties")]
+pub trait EnvPropertiesLoad<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn env_load_properties(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_properties(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_properties_with(
+        path: &Path,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+    fn env_parse_properties_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+}
+
+/// 二进制格式只有文件读写，没有环境变量替换
+#[cfg(feature = "msgpack")]
+pub trait MsgpackIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_msgpack(path: &Path) -> OrionConfResult<T>;
+    fn save_msgpack(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_msgpack_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+#[cfg(feature = "cbor")]
+pub trait CborIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    fn load_cbor(path: &Path) -> OrionConfResult<T>;
+    fn save_cbor(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_cbor_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+}
+
+/// 由 `T` 生成 JSON Schema，供编辑器校验和补全手写的配置文件
+#[cfg(feature = "schema")]
+pub trait SchemaIO<T>
+where
+    T: schemars::JsonSchema,
+{
+    fn json_schema() -> serde_json::Value;
+    fn save_json_schema(path: &Path) -> OrionConfResult<()>;
 }
 
 #[cfg(feature = "toml")]
@@ -76,6 +267,9 @@ where
 {
     fn load_toml(path: &Path) -> OrionConfResult<T>;
     fn save_toml(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_toml_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+    /// 将变化写回已有文件，保留其中的注释、空白和键顺序；文件不存在时等同 `save_toml`
+    fn update_toml(&self, path: &Path) -> OrionConfResult<()>;
 }
 
 #[cfg(feature = "toml")]
@@ -85,6 +279,12 @@ where
 {
     fn env_load_toml(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
     fn env_parse_toml(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_toml_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_toml_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
 }
 
 #[cfg(feature = "toml")]
@@ -103,6 +303,9 @@ where
 {
     fn load_yaml(path: &Path) -> OrionConfResult<T>;
     fn save_yaml(&self, path: &Path) -> OrionConfResult<()>;
+    fn save_yaml_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()>;
+    /// 只改写已有文件中 `key_paths` 对应的值，其余内容逐字节保持不变
+    fn patch_yaml(&self, path: &Path, key_paths: &[&str]) -> OrionConfResult<()>;
 }
 
 #[cfg(feature = "yaml")]
@@ -112,73 +315,145 @@ where
 {
     fn env_load_yaml(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
     fn env_parse_yaml(content: &str, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_yaml_with(path: &Path, dict: &EnvDict, options: &EnvOptions) -> OrionConfResult<T>;
+    fn env_parse_yaml_with(
+        content: &str,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
 }
 
 pub trait LoadHook {
     fn loaded_event_do(&mut self) {}
 }
 
-// 默认实现选择逻辑 - 基于特性优先级
-impl<T> ConfigIO<T> for T
+// 保存前钩子 - 在序列化前作用于对象副本，可用于剔除计算字段
+pub trait SaveHook {
+    fn saving_event_do(&mut self) {}
+}
+
+// 带钩子的配置 trait - 加载成功后调用 LoadHook，保存前调用 SaveHook
+pub trait HookedConfigIO<T>
 where
-    T: serde::de::DeserializeOwned + serde::Serialize,
+    T: serde::de::DeserializeOwned + serde::Serialize + LoadHook,
 {
-    #[cfg(feature = "yaml")]
-    fn load_conf(path: &Path) -> OrionConfResult<T> {
-        T::load_yaml(path)
-    }
+    fn load_conf_hooked(path: &Path) -> OrionConfResult<T>;
+    fn env_load_conf_hooked(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_parse_conf_hooked(
+        content: &str,
+        format: ConfFormat,
+        dict: &EnvDict,
+    ) -> OrionConfResult<T>;
+    fn save_conf_hooked(&self, path: &Path) -> OrionConfResult<()>
+    where
+        T: SaveHook + Clone;
+}
 
-    #[cfg(all(feature = "toml", not(feature = "yaml")))]
-    fn load_conf(path: &Path) -> OrionConfResult<T> {
-        T::load_toml(path)
-    }
+// 语义校验 - 检查类型之外的规则（取值范围、字段间约束），一次返回全部违规项
+pub trait Validate {
+    fn validate(&self) -> Result<(), Vec<Violation>>;
+}
 
-    #[cfg(all(feature = "json", not(any(feature = "yaml", feature = "toml"))))]
-    fn load_conf(path: &Path) -> OrionConfResult<T> {
-        T::load_json(path)
-    }
+// 带校验的配置 trait - 加载成功后调用 Validate，违规时返回 ConfIOReason::Validation
+pub trait ValidatedConfigIO<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize + Validate,
+{
+    fn load_conf_validated(path: &Path) -> OrionConfResult<T>;
+    fn env_load_conf_validated(path: &Path, dict: &EnvDict) -> OrionConfResult<T>;
+    fn env_load_conf_validated_with(
+        path: &Path,
+        dict: &EnvDict,
+        options: &EnvOptions,
+    ) -> OrionConfResult<T>;
+    fn env_parse_conf_validated(
+        content: &str,
+        format: ConfFormat,
+        dict: &EnvDict,
+    ) -> OrionConfResult<T>;
+}
 
-    #[cfg(all(
-        feature = "ini",
-        not(any(feature = "yaml", feature = "toml", feature = "json"))
-    ))]
+// 默认实现选择逻辑 - 优先按扩展名，未知扩展名时基于特性优先级
+impl<T> ConfigIO<T> for T
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
     fn load_conf(path: &Path) -> OrionConfResult<T> {
-        T::load_ini(path)
-    }
-
-    #[cfg(not(any(feature = "yaml", feature = "toml", feature = "json", feature = "ini")))]
-    fn load_conf(_path: &Path) -> OrionConfResult<T> {
-        use crate::error::ConfIOReason;
-        Err(ConfIOReason::NoFormatEnabled.to_err())
-    }
-
-    #[cfg(feature = "yaml")]
-    fn save_conf(&self, path: &Path) -> OrionConfResult<()> {
-        self.save_yaml(path)
-    }
-
-    #[cfg(all(feature = "toml", not(feature = "yaml")))]
-    fn save_conf(&self, path: &Path) -> OrionConfResult<()> {
-        self.save_toml(path)
-    }
-
-    #[cfg(all(feature = "json", not(any(feature = "yaml", feature = "toml"))))]
-    fn save_conf(&self, path: &Path) -> OrionConfResult<()> {
-        self.save_json(path)
+        match ConfFormat::resolve(path)? {
+            #[cfg(feature = "yaml")]
+            ConfFormat::Yaml => T::load_yaml(path),
+            #[cfg(feature = "toml")]
+            ConfFormat::Toml => T::load_toml(path),
+            #[cfg(feature = "json")]
+            ConfFormat::Json => T::load_json(path),
+            #[cfg(feature = "json5")]
+            ConfFormat::Json5 => T::load_json5(path),
+            #[cfg(feature = "ini")]
+            ConfFormat::Ini => T::load_ini(path),
+            #[cfg(feature = "ron")]
+            ConfFormat::Ron => T::load_ron(path),
+            #[cfg(feature = "dotenv")]
+            ConfFormat::Dotenv => T::load_dotenv(path),
+            #[cfg(feature = "hcl")]
+            ConfFormat::Hcl => T::load_hcl(path),
+            #[cfg(feature = "properties")]
+            ConfFormat::Properties => T::load_properties(path),
+            #[cfg(feature = "msgpack")]
+            ConfFormat::Msgpack => T::load_msgpack(path),
+            #[cfg(feature = "cbor")]
+            ConfFormat::Cbor => T::load_cbor(path),
+            #[allow(unreachable_patterns)]
+            fmt => Err(ConfIOReason::FormatNotEnabled(fmt).to_err()),
+        }
     }
 
-    #[cfg(all(
-        feature = "ini",
-        not(any(feature = "yaml", feature = "toml", feature = "json"))
-    ))]
     fn save_conf(&self, path: &Path) -> OrionConfResult<()> {
-        self.save_ini(path)
+        self.save_conf_with(path, &SaveOptions::default())
     }
 
-    #[cfg(not(any(feature = "yaml", feature = "toml", feature = "json", feature = "ini")))]
-    fn save_conf(&self, _path: &Path) -> OrionConfResult<()> {
-        use crate::error::ConfIOReason;
-        Err(ConfIOReason::NoFormatEnabled.to_err())
+    #[cfg_attr(
+        not(any(
+            feature = "yaml",
+            feature = "toml",
+            feature = "json",
+            feature = "json5",
+            feature = "ini",
+            feature = "ron",
+            feature = "dotenv",
+            feature = "hcl",
+            feature = "properties",
+            feature = "msgpack",
+            feature = "cbor"
+        )),
+        allow(unused_variables)
+    )]
+    fn save_conf_with(&self, path: &Path, options: &SaveOptions) -> OrionConfResult<()> {
+        match ConfFormat::resolve(path)? {
+            #[cfg(feature = "yaml")]
+            ConfFormat::Yaml => self.save_yaml_with(path, options),
+            #[cfg(feature = "toml")]
+            ConfFormat::Toml => self.save_toml_with(path, options),
+            #[cfg(feature = "json")]
+            ConfFormat::Json => self.save_json_with(path, options),
+            #[cfg(feature = "json5")]
+            ConfFormat::Json5 => self.save_json5_with(path, options),
+            #[cfg(feature = "ini")]
+            ConfFormat::Ini => self.save_ini_with(path, options),
+            #[cfg(feature = "ron")]
+            ConfFormat::Ron => self.save_ron_with(path, options),
+            #[cfg(feature = "dotenv")]
+            ConfFormat::Dotenv => self.save_dotenv_with(path, options),
+            #[cfg(feature = "hcl")]
+            ConfFormat::Hcl => self.save_hcl_with(path, options),
+            #[cfg(feature = "properties")]
+            ConfFormat::Properties => self.save_properties_with(path, options),
+            #[cfg(feature = "msgpack")]
+            ConfFormat::Msgpack => self.save_msgpack_with(path, options),
+            #[cfg(feature = "cbor")]
+            ConfFormat::Cbor => self.save_cbor_with(path, options),
+            #[allow(unreachable_patterns)]
+            fmt => Err(ConfIOReason::FormatNotEnabled(fmt).to_err()),
+        }
     }
 }
 
@@ -0,0 +1,426 @@
+//! 配置校验：违规项，以及反序列化前按 JSON Schema 校验值树（`schema-validate` 特性）
+
+use derive_getters::Getters;
+use serde_derive::Serialize;
+use std::{fmt, path::PathBuf};
+
+/// 一条校验违规：出错的键路径、描述和（可用时）文件中的位置
+#[derive(Clone, Debug, Serialize, PartialEq, Getters)]
+pub struct Violation {
+    key_path: String,
+    message: String,
+    path: Option<PathBuf>,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl Violation {
+    /// `key_path` 使用点分形式，如 `nested_config.retry_count`；根节点为空字符串
+    pub fn new(key_path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key_path: key_path.into(),
+            message: message.into(),
+            path: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// 行列均从 1 开始，列按字符计
+    pub fn with_location(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key_path.is_empty() {
+            write!(f, "{}", self.message)?;
+        } else {
+            write!(f, "{}: {}", self.key_path, self.message)?;
+        }
+        match (&self.path, self.line, self.column) {
+            (Some(path), Some(line), Some(column)) => {
+                write!(f, " ({}:{line}:{column})", path.display())
+            }
+            (Some(path), ..) => write!(f, " ({})", path.display()),
+            (None, Some(line), Some(column)) => write!(f, " (line {line}, column {column})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 渲染全部违规项，每项一行
+pub(crate) fn render_violations(violations: &[Violation]) -> String {
+    let mut out = format!(
+        "config validation failed with {} violation(s)",
+        violations.len()
+    );
+    for violation in violations {
+        out.push_str(&format!("\n  - {violation}"));
+    }
+    out
+}
+
+#[cfg(feature = "schema-validate")]
+pub use schema::SchemaValidator;
+
+#[cfg(feature = "schema-validate")]
+mod schema {
+    use jsonschema::{
+        JsonType, error::TypeKind, error::ValidationErrorKind, paths::LocationSegment,
+    };
+    use orion_error::{ContextRecord, ErrorOwe, ErrorWith, OperationContext, ToStructError};
+    use serde_json::{Number, Value};
+    use std::{fs, path::Path};
+
+    use super::Violation;
+    use crate::diagnostic::parse_error;
+    use crate::error::{ConfIOReason, OrionConfResult};
+    use crate::format::ConfFormat;
+    use crate::value::{from_value_lenient, index_list, parse_bool};
+
+    /// 反序列化前按 JSON Schema 校验值树，一次返回全部违规项
+    ///
+    /// INI、dotenv、properties 等只有字符串值的格式，字符串叶子会先按 schema 期望的类型
+    /// 转换为数字或布尔值再校验，与宽松反序列化的规则一致。
+    ///
+    /// 文件中的位置按键路径在原文中依次查找键名得出，是尽力而为的定位；
+    /// 缺少必填字段时指向其所在的父节点。
+    #[derive(Clone, Debug)]
+    pub struct SchemaValidator {
+        validator: jsonschema::Validator,
+    }
+
+    impl SchemaValidator {
+        /// 使用给定的 schema；schema 本身无效时返回错误
+        pub fn new(schema: &Value) -> OrionConfResult<Self> {
+            let validator = jsonschema::validator_for(schema)
+                .map_err(|e| ConfIOReason::from(format!("invalid json schema: {e}")).to_err())?;
+            Ok(Self { validator })
+        }
+
+        /// 使用由 `T` 生成的 schema；生成的 schema 无法编译时返回错误
+        pub fn for_type<T>() -> OrionConfResult<Self>
+        where
+            T: schemars::JsonSchema,
+        {
+            Self::new(&<T as crate::traits::SchemaIO<T>>::json_schema())
+        }
+
+        /// 校验值树，不做类型转换，违规项不带位置
+        pub fn validate(&self, value: &Value) -> Result<(), Vec<Violation>> {
+            let violations: Vec<Violation> = self
+                .validator
+                .iter_errors(value)
+                .map(|err| {
+                    let (key_path, _) = key_path(&err);
+                    Violation::new(key_path, err.to_string())
+                })
+                .collect();
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations)
+            }
+        }
+
+        /// 读取文件，校验通过后再反序列化为 `T`
+        pub fn load<T>(&self, path: &Path) -> OrionConfResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            let mut ctx =
+                OperationContext::want("load object with schema validation").with_auto_log();
+            ctx.record("from path", path);
+            let format = ConfFormat::resolve(path).with(&ctx)?;
+            if format.is_binary() {
+                return Err(ConfIOReason::BinaryFormat(format).to_err()).with(&ctx);
+            }
+            let content = fs::read_to_string(path).owe_res().with(&ctx)?;
+            let loaded = self.check(format, &content, Some(path)).with(&ctx)?;
+            ctx.mark_suc();
+            Ok(loaded)
+        }
+
+        /// 解析文本，校验通过后再反序列化为 `T`
+        pub fn parse<T>(&self, format: ConfFormat, content: &str) -> OrionConfResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            let mut ctx =
+                OperationContext::want("parse object with schema validation").with_auto_log();
+            ctx.record("source", format!("inline {format} content"));
+            let loaded = self.check(format, content, None).with(&ctx)?;
+            ctx.mark_suc();
+            Ok(loaded)
+        }
+
+        fn check<T>(
+            &self,
+            format: ConfFormat,
+            content: &str,
+            path: Option<&Path>,
+        ) -> OrionConfResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            let mut value = format.parse_value(content, path)?;
+            self.validate_value(&mut value).map_err(|violations| {
+                let located = violations
+                    .into_iter()
+                    .map(|(violation, keys)| locate_violation(violation, content, path, &keys))
+                    .collect();
+                ConfIOReason::Validation(located).to_err()
+            })?;
+            from_value_lenient(value).map_err(|e| parse_error(format, path, content, e))
+        }
+
+        /// 转换字符串叶子后校验；违规项附带用于定位的键名序列
+        pub(crate) fn validate_value(
+            &self,
+            value: &mut Value,
+        ) -> Result<(), Vec<(Violation, Vec<String>)>> {
+            coerce_strings(&self.validator, value);
+            let violations: Vec<_> = self
+                .validator
+                .iter_errors(value)
+                .map(|err| {
+                    let (key_path, keys) = key_path(&err);
+                    (Violation::new(key_path, err.to_string()), keys)
+                })
+                .collect();
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations)
+            }
+        }
+    }
+
+    /// 类型不符的值按期望的类型转换：字符串叶子转为数字或布尔值，下标键（`hosts.0`）映射转为数组，
+    /// 期望数组的非空字符串转为单元素数组
+    ///
+    /// 转换出的数组元素要再校验一轮才会报告各自的类型错误，因此重复到没有可转换的值为止。
+    fn coerce_strings(validator: &jsonschema::Validator, value: &mut Value) {
+        loop {
+            let coercions: Vec<(String, Value)> = validator
+                .iter_errors(value)
+                .filter_map(|err| {
+                    let ValidationErrorKind::Type { kind } = err.kind() else {
+                        return None;
+                    };
+                    let expects = |ty| match kind {
+                        TypeKind::Single(single) => *single == ty,
+                        TypeKind::Multiple(set) => set.contains(ty),
+                    };
+                    let pointer = err.instance_path().as_str().to_string();
+                    if let Some(map) = err.instance().as_object()
+                        && expects(JsonType::Array)
+                    {
+                        let list = index_list(map.clone()).ok()?;
+                        return Some((pointer, Value::Array(list)));
+                    }
+                    let text = err.instance().as_str()?;
+                    if expects(JsonType::Array) && !text.is_empty() {
+                        return Some((pointer, Value::Array(vec![Value::from(text)])));
+                    }
+                    let coerced = if expects(JsonType::Integer) {
+                        let text = text.trim();
+                        text.parse::<i64>()
+                            .map(Number::from)
+                            .or_else(|_| text.parse::<u64>().map(Number::from))
+                            .ok()
+                            .map(Value::Number)
+                    } else {
+                        None
+                    };
+                    let coerced = coerced
+                        .or_else(|| {
+                            expects(JsonType::Number)
+                                .then(|| text.trim().parse::<f64>().ok().and_then(Number::from_f64))
+                                .flatten()
+                                .map(Value::Number)
+                        })
+                        .or_else(|| {
+                            expects(JsonType::Boolean)
+                                .then(|| parse_bool(text))
+                                .flatten()
+                                .map(Value::Bool)
+                        })?;
+                    Some((pointer, coerced))
+                })
+                .collect();
+            if coercions.is_empty() {
+                break;
+            }
+            for (pointer, coerced) in coercions {
+                if let Some(slot) = value.pointer_mut(&pointer) {
+                    *slot = coerced;
+                }
+            }
+        }
+    }
+
+    /// 点分键路径（数组下标写作 `[0]`），以及用于在原文中定位的键名序列
+    ///
+    /// 缺少必填字段时键路径指向该字段，定位用的键名止于父节点。
+    fn key_path(err: &jsonschema::ValidationError<'_>) -> (String, Vec<String>) {
+        let mut key_path = String::new();
+        let mut keys = Vec::new();
+        for segment in err.instance_path() {
+            match segment {
+                LocationSegment::Property(name) => {
+                    if !key_path.is_empty() {
+                        key_path.push('.');
+                    }
+                    key_path.push_str(&name);
+                    keys.push(name.into_owned());
+                }
+                LocationSegment::Index(index) => key_path.push_str(&format!("[{index}]")),
+            }
+        }
+        if let ValidationErrorKind::Required { property } = err.kind()
+            && let Some(name) = property.as_str()
+        {
+            if !key_path.is_empty() {
+                key_path.push('.');
+            }
+            key_path.push_str(name);
+        }
+        (key_path, keys)
+    }
+
+    fn locate_violation(
+        violation: Violation,
+        content: &str,
+        path: Option<&Path>,
+        keys: &[String],
+    ) -> Violation {
+        let violation = match path {
+            Some(path) => violation.with_path(path),
+            None => violation,
+        };
+        match locate(content, keys) {
+            Some((line, column)) => violation.with_location(line, column),
+            None => violation,
+        }
+    }
+
+    /// 在原文中依次查找各级键名，返回最后一级键名的 `(行, 列)`
+    fn locate(content: &str, keys: &[String]) -> Option<(usize, usize)> {
+        let mut from = 0;
+        let mut found = None;
+        for key in keys {
+            let offset = find_key(&content[from..], key)? + from;
+            found = Some(offset);
+            from = offset + key.len();
+        }
+        let offset = found?;
+        let before = &content[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Some((
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        ))
+    }
+
+    /// 查找作为完整单词出现的键名
+    fn find_key(text: &str, key: &str) -> Option<usize> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        text.match_indices(key).map(|(i, _)| i).find(|&i| {
+            !text[..i].chars().next_back().is_some_and(is_word)
+                && !text[i + key.len()..].chars().next().is_some_and(is_word)
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        fn schema() -> SchemaValidator {
+            SchemaValidator::new(&json!({
+                "type": "object",
+                "required": ["name", "server"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "debug": {"type": "boolean"},
+                    "server": {
+                        "type": "object",
+                        "required": ["port"],
+                        "properties": {
+                            "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                            "hosts": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn test_validate_collects_all_violations() {
+            let value = json!({"debug": "yes", "server": {"port": 0, "hosts": ["a", 1]}});
+            let violations = schema().validate(&value).unwrap_err();
+            let mut paths: Vec<&str> = violations.iter().map(|v| v.key_path().as_str()).collect();
+            paths.sort();
+            assert_eq!(paths, ["debug", "name", "server.hosts[1]", "server.port"]);
+        }
+
+        #[test]
+        fn test_coerce_strings_by_schema_type() {
+            let mut value = json!({"name": "svc", "debug": "yes", "server": {"port": "8080"}});
+            schema().validate_value(&mut value).unwrap();
+            assert_eq!(
+                value,
+                json!({"name": "svc", "debug": true, "server": {"port": 8080}})
+            );
+        }
+
+        #[test]
+        fn test_coerce_index_maps_to_arrays() {
+            let validator = SchemaValidator::new(&json!({
+                "type": "object",
+                "properties": {
+                    "ports": {"type": "array", "items": {"type": "integer"}},
+                    "labels": {"type": "object"}
+                }
+            }))
+            .unwrap();
+            let mut value = json!({"ports": {"1": "443", "0": "80"}, "labels": {"0": "zero"}});
+            validator.validate_value(&mut value).unwrap();
+            assert_eq!(value, json!({"ports": [80, 443], "labels": {"0": "zero"}}));
+
+            let mut value = json!({"ports": "8080"});
+            validator.validate_value(&mut value).unwrap();
+            assert_eq!(value, json!({"ports": [8080]}));
+        }
+
+        #[test]
+        fn test_locate_nested_keys() {
+            let content = "name: port\nserver:\n  hosts: [a]\n  port: 0\n";
+            let keys = ["server".to_string(), "port".to_string()];
+            assert_eq!(locate(content, &keys), Some((4, 3)));
+            assert_eq!(
+                locate("a.port=1\n", &["a".into(), "port".into()]),
+                Some((1, 3))
+            );
+            assert_eq!(locate(content, &["missing".into()]), None);
+            assert_eq!(locate(content, &[]), None);
+        }
+
+        #[test]
+        fn test_invalid_schema() {
+            let err = SchemaValidator::new(&json!({"type": 5})).unwrap_err();
+            assert!(err.reason().to_string().starts_with("invalid json schema"));
+        }
+    }
+}
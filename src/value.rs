@@ -0,0 +1,597 @@
+//! 宽松的值树反序列化
+//!
+//! 字符串叶子会按目标类型转换为数字或布尔值，用于环境变量覆盖等只能提供字符串的来源。
+
+use serde::de::{
+    self, Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess,
+    SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde_json::{Error, Map, Number, Value};
+
+use crate::diagnostic::{Tracked, deserialize_tracked};
+
+/// 从值树反序列化 `T`，目标为数字或布尔类型的字符串叶子会被解析转换
+pub(crate) fn from_value_lenient<T>(value: Value) -> Result<T, Tracked<Error>>
+where
+    T: DeserializeOwned,
+{
+    deserialize_tracked(Lenient(value))
+}
+
+/// 解析布尔字符串，支持 true/false、yes/no、on/off、1/0（大小写不敏感）
+pub(crate) fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// 格式无关的值树
+///
+/// 与直接反序列化为 `Value` 不同，带标签的枚举（YAML 的 `!Slow 3`）按外部标记保存为
+/// `{"Slow": 3}`，宽松反序列化时可以还原为枚举。
+pub(crate) struct Tree(pub(crate) Value);
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(TreeVisitor).map(Tree)
+    }
+}
+
+struct TreeVisitor;
+
+impl<'de> Visitor<'de> for TreeVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any configuration value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        // 与 serde_json 一致，NaN 和无穷大记为 null
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Tree::deserialize(deserializer).map(|tree| tree.0)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Tree::deserialize(deserializer).map(|tree| tree.0)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut list = Vec::new();
+        while let Some(Tree(item)) = seq.next_element()? {
+            list.push(item);
+        }
+        Ok(Value::Array(list))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Map::new();
+        while let Some((key, Tree(value))) = map.next_entry::<String, Tree>()? {
+            entries.insert(key, value);
+        }
+        Ok(Value::Object(entries))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (variant, content) = data.variant::<String>()?;
+        let Tree(value) = content.newtype_variant()?;
+        Ok(Value::Object(Map::from_iter([(variant, value)])))
+    }
+}
+
+struct Lenient(Value);
+
+macro_rules! lenient_number {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0 {
+                    Value::String(text) => match text.trim().parse::<$ty>() {
+                        Ok(number) => visitor.$visit(number),
+                        Err(_) => Err(de::Error::invalid_type(Unexpected::Str(&text), &visitor)),
+                    },
+                    other => other.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Array(list) => visitor.visit_seq(LenientSeq(list.into_iter())),
+            Value::Object(map) => visitor.visit_map(LenientMap {
+                iter: map.into_iter(),
+                value: None,
+            }),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::String(text) => match parse_bool(&text) {
+                Some(flag) => visitor.visit_bool(flag),
+                None => Err(de::Error::invalid_type(Unexpected::Str(&text), &visitor)),
+            },
+            other => other.deserialize_bool(visitor),
+        }
+    }
+
+    lenient_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(Lenient(other)),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            // 文本格式以空值表示空数组
+            Value::String(text) if text.is_empty() => {
+                visitor.visit_seq(LenientSeq(Vec::new().into_iter()))
+            }
+            // 只出现一次的键（INI 中不带 `[]` 的 `hosts=a`）读作单元素数组
+            Value::String(text) => {
+                visitor.visit_seq(LenientSeq(vec![Value::String(text)].into_iter()))
+            }
+            // properties 的 `hosts.0` / `hosts.1` 等下标键按下标顺序读作数组
+            Value::Object(map) => match index_list(map) {
+                Ok(list) => visitor.visit_seq(LenientSeq(list.into_iter())),
+                Err(map) => Value::Object(map).deserialize_seq(visitor),
+            },
+            other => Lenient(other).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            // 文本格式以空值表示空映射或空结构体
+            Value::String(text) if text.is_empty() => visitor.visit_map(LenientMap {
+                iter: serde_json::Map::new().into_iter(),
+                value: None,
+            }),
+            other => Lenient(other).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::String(variant) => visitor.visit_enum(LenientEnum {
+                variant,
+                value: Value::Null,
+            }),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().unwrap_or_default();
+                visitor.visit_enum(LenientEnum { variant, value })
+            }
+            other => other.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+/// 键恰好为 `0..n` 的映射按下标顺序转为数组，否则原样返回
+pub(crate) fn index_list(mut map: Map<String, Value>) -> Result<Vec<Value>, Map<String, Value>> {
+    let is_list =
+        !map.is_empty() && (0..map.len()).all(|index| map.contains_key(index.to_string().as_str()));
+    if !is_list {
+        return Err(map);
+    }
+    Ok((0..map.len())
+        .filter_map(|index| map.remove(index.to_string().as_str()))
+        .collect())
+}
+
+struct LenientSeq(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for LenientSeq {
+    type Error = Error;
+
+    fn next_element_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        match self.0.next() {
+            Some(value) => seed.deserialize(Lenient(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct LenientMap {
+    iter: serde_json::map::IntoIter,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for LenientMap {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Lenient(Value::String(key))).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Lenient(self.value.take().unwrap_or(Value::Null)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct LenientEnum {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for LenientEnum {
+    type Error = Error;
+    type Variant = Lenient;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, Lenient), Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(Lenient(Value::String(self.variant)))?;
+        Ok((variant, Lenient(self.value)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Lenient {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.0 {
+            Value::Null => Ok(()),
+            other => Err(de::Error::invalid_type(unexpected(&other), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<S>(self, seed: S) -> Result<S::Value, Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Bool(flag) => Unexpected::Bool(*flag),
+        Value::Number(_) => Unexpected::Other("number"),
+        Value::String(text) => Unexpected::Str(text),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::Deserialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Target {
+        port: u16,
+        ratio: f64,
+        enabled: bool,
+        name: String,
+        retries: Option<i32>,
+        tags: Vec<u8>,
+        mode: Mode,
+        ids: BTreeMap<u32, String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Mode {
+        Fast,
+        Limit(u32),
+    }
+
+    #[test]
+    fn test_from_value_lenient_coerces_strings() {
+        let value = json!({
+            "port": "8080",
+            "ratio": "0.5",
+            "enabled": "Yes",
+            "name": "42",
+            "retries": "-3",
+            "tags": ["1", 2],
+            "mode": {"Limit": "10"},
+            "ids": {"7": "seven"}
+        });
+        let target: Target = from_value_lenient(value).unwrap();
+        assert_eq!(
+            target,
+            Target {
+                port: 8080,
+                ratio: 0.5,
+                enabled: true,
+                name: "42".to_string(),
+                retries: Some(-3),
+                tags: vec![1, 2],
+                mode: Mode::Limit(10),
+                ids: BTreeMap::from([(7, "seven".to_string())]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_value_lenient_keeps_native_values() {
+        let value = json!({
+            "port": 80,
+            "ratio": 1.5,
+            "enabled": false,
+            "name": "svc",
+            "retries": null,
+            "tags": [],
+            "mode": "Fast",
+            "ids": {}
+        });
+        let target: Target = from_value_lenient(value).unwrap();
+        assert_eq!(target.port, 80);
+        assert!(!target.enabled);
+        assert_eq!(target.retries, None);
+        assert_eq!(target.mode, Mode::Fast);
+    }
+
+    #[test]
+    fn test_from_value_lenient_reads_empty_text_as_empty_container() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Empty {
+            tags: Vec<String>,
+            ids: BTreeMap<String, u8>,
+            inner: Inner,
+            name: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            note: Option<String>,
+        }
+
+        let value = json!({"tags": "", "ids": "", "inner": "", "name": ""});
+        let target: Empty = from_value_lenient(value).unwrap();
+        assert!(target.tags.is_empty());
+        assert!(target.ids.is_empty());
+        assert_eq!(target.inner, Inner { note: None });
+        assert_eq!(target.name, "");
+
+        let result: Result<Vec<u8>, _> = from_value_lenient(json!("1"));
+        assert_eq!(result.unwrap(), [1]);
+        let result: Result<Vec<u8>, _> = from_value_lenient(json!("x"));
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_tree_keeps_tagged_enums() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Shape {
+            Point,
+            Circle(f64),
+            Rect { w: u32, h: u32 },
+            Pair(u8, u8),
+        }
+
+        let content = "a: Point\nb: !Circle 1.5\nc: !Rect {w: 2, h: 3}\nd: !Pair [1, 2]\n";
+        let Tree(value) = serde_yaml::from_str(content).unwrap();
+        assert_eq!(
+            value,
+            json!({"a": "Point", "b": {"Circle": 1.5}, "c": {"Rect": {"w": 2, "h": 3}}, "d": {"Pair": [1, 2]}})
+        );
+        let shapes: BTreeMap<String, Shape> = from_value_lenient(value).unwrap();
+        assert_eq!(shapes["a"], Shape::Point);
+        assert_eq!(shapes["b"], Shape::Circle(1.5));
+        assert_eq!(shapes["c"], Shape::Rect { w: 2, h: 3 });
+        assert_eq!(shapes["d"], Shape::Pair(1, 2));
+    }
+
+    #[test]
+    fn test_from_value_lenient_reads_index_maps_by_target() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Indexed {
+            hosts: Vec<String>,
+            pair: (u8, String),
+            labels: BTreeMap<String, String>,
+        }
+
+        let value = json!({
+            "hosts": {"1": "b", "0": "a"},
+            "pair": {"0": "7", "1": "x"},
+            "labels": {"0": "zero"}
+        });
+        let target: Indexed = from_value_lenient(value).unwrap();
+        assert_eq!(target.hosts, ["a", "b"]);
+        assert_eq!(target.pair, (7, "x".to_string()));
+        assert_eq!(target.labels["0"], "zero");
+
+        let result: Result<Vec<String>, _> = from_value_lenient(json!({"1": "b"}));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_value_lenient_rejects_bad_numbers() {
+        let result: Result<BTreeMap<String, u16>, _> =
+            from_value_lenient(json!({"port": "not_a_port"}));
+        let err = result.unwrap_err().to_string();
+        assert!(err.starts_with("port: "));
+        assert!(err.contains("not_a_port"));
+    }
+}